@@ -1,13 +1,34 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::interpreter::control_flow::ControlFlow;
+use crate::interpreter::environment::Environment;
 use crate::interpreter::Interpreter;
-use crate::parser::expr::LiteralValue;
+use crate::interpreter::runtime_error::RuntimeError;
 use crate::parser::stmt::Stmt;
 
 impl Interpreter{
-    pub fn evaluate_block(&mut self, stmts: Vec<Stmt>)  {
-        self.environment.push_scope();
+    pub(super) fn evaluate_block(&mut self, stmts: &[Stmt]) -> Result<ControlFlow, RuntimeError> {
+        let environment = Environment::with_enclosing(self.environment.clone());
+        self.evaluate_in(Rc::new(RefCell::new(environment)), stmts)
+    }
+
+    /// Runs statements in the given scope, then returns to the current one.
+    pub(super) fn evaluate_in(&mut self, environment: Rc<RefCell<Environment>>, stmts: &[Stmt]) -> Result<ControlFlow, RuntimeError> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let flow = self.evaluate_stmts(stmts);
+        self.environment = previous;
+        flow
+    }
+
+    /// Runs statements in the current scope, stopping early on `return`,
+    /// `break` or `continue`.
+    pub(super) fn evaluate_stmts(&mut self, stmts: &[Stmt]) -> Result<ControlFlow, RuntimeError> {
         for stmt in stmts {
-            self.evaluate(stmt);
+            let flow = self.evaluate(stmt)?;
+            if !matches!(flow, ControlFlow::Normal) {
+                return Ok(flow);
+            }
         }
-        self.environment.pop_scope();
+        Ok(ControlFlow::Normal)
     }
 }
\ No newline at end of file
@@ -0,0 +1,50 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::interpreter::control_flow::ControlFlow;
+use crate::interpreter::environment::Environment;
+use crate::interpreter::function::Function;
+use crate::interpreter::Interpreter;
+use crate::interpreter::runtime_error::RuntimeError;
+use crate::parser::expr::{Expr, LiteralValue};
+use crate::scanner::token::Token;
+
+impl Interpreter {
+    pub(super) fn evaluate_call(&mut self, callee: &Expr, paren: &Token, arguments: &[Expr]) -> Result<LiteralValue, RuntimeError> {
+        let callee = self.evaluate_expression(callee)?;
+        let arguments = arguments.iter()
+            .map(|argument| self.evaluate_expression(argument))
+            .collect::<Result<Vec<LiteralValue>, RuntimeError>>()?;
+
+        let function = match callee {
+            LiteralValue::Function(function) => function,
+            other => {
+                let message = format!("Can only call functions, not '{}'.", other);
+                return Err(RuntimeError::new(paren.clone(), &message));
+            }
+        };
+        if arguments.len() != function.arity() {
+            let message = format!("Expected {} arguments but got {}.", function.arity(), arguments.len());
+            return Err(RuntimeError::new(paren.clone(), &message));
+        }
+        if self.call_depth >= self.max_call_depth {
+            return Err(RuntimeError::new(paren.clone(), "Stack overflow."));
+        }
+        self.call_depth += 1;
+        let result = self.call_function(function, arguments);
+        self.call_depth -= 1;
+        result
+    }
+
+    /// Runs the body with the parameters bound in a fresh scope nested in the
+    /// function's closure, and returns the `return` value (nil if none).
+    /// The resolver gives the parameters the first slots, in order.
+    fn call_function(&mut self, function: Rc<Function>, arguments: Vec<LiteralValue>) -> Result<LiteralValue, RuntimeError> {
+        let environment = Environment::with_values(arguments, function.get_closure().clone());
+        let flow = self.evaluate_in(Rc::new(RefCell::new(environment)), function.get_body())?;
+        match flow {
+            ControlFlow::Return(value) => Ok(value),
+            // the resolver keeps `break`/`continue` from leaving a function body
+            ControlFlow::Normal | ControlFlow::Break | ControlFlow::Continue => Ok(LiteralValue::Nil),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,14 @@
+use crate::parser::expr::LiteralValue;
+
+/// How a statement finished. Anything but `Normal` unwinds the enclosing
+/// statements until it reaches the construct that handles it.
+#[derive(Debug)]
+pub enum ControlFlow {
+    Normal,
+    /// `return`, handled by the function call
+    Return(LiteralValue),
+    /// `break`, handled by the innermost loop
+    Break,
+    /// `continue`, handled by the innermost loop
+    Continue,
+}
\ No newline at end of file
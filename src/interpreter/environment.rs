@@ -1,49 +1,67 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
 use crate::parser::expr::LiteralValue;
 
+/// One local scope, linked to the scope it is nested in. Variables live in
+/// the slot the resolver assigned them, so lookups index instead of hashing
+/// names. A slot stays None until its declaration runs, which a skipped one
+/// never does, e.g. `if (c) var x;`. Globals are kept by the interpreter, not here.
+/// Scopes are shared (`Rc<RefCell<..>>`) so a function value can keep the
+/// scope it was defined in alive after that block or call has finished.
 #[derive(Debug)]
 pub struct Environment{
-    scopes : Vec<HashMap<String, LiteralValue>>,
+    slots: Vec<Option<LiteralValue>>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Environment{
     pub fn new() -> Environment {
-        Environment{ scopes : vec![HashMap::new()] } // global scope
+        Environment{ slots: vec![], enclosing: None } // top level, holds no locals
     }
 
-    pub fn get(&self, name: &str) -> Option<LiteralValue> {
-
-        for scope in self.scopes.iter().rev() {
-            if let Some(value) = scope.get(name) {
-                return Some(value.clone());
-            }
-        }
-        None
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Environment {
+        Environment{ slots: vec![], enclosing: Some(enclosing) }
     }
-    pub fn set(&mut self, name: String, value: LiteralValue) {
-        self.scopes.last_mut().unwrap().insert(name, value);
+
+    /// A scope whose first slots are already filled, e.g. a call's arguments.
+    pub fn with_values(values: Vec<LiteralValue>, enclosing: Rc<RefCell<Environment>>) -> Environment {
+        Environment{ slots: values.into_iter().map(Some).collect(), enclosing: Some(enclosing) }
     }
 
-    pub fn update(&mut self, name: String, value: LiteralValue) {
-        for scope in self.scopes.iter_mut().rev() {
-            if scope.contains_key(&name) {
-                scope.insert(name.clone(), value.clone());
-            }
+    /// Reads the slot `depth` scopes out, as computed by the resolver.
+    /// None if the declaration owning the slot has not run yet.
+    pub fn get_at(&self, depth: usize, slot: usize) -> Option<LiteralValue> {
+        if depth == 0 {
+            return self.slots.get(slot).cloned().flatten();
         }
-    }
-    pub fn contains_in_current_scope(&mut self, name: &str) -> bool {
-        if let Some(scope) = self.scopes.last() {
-            scope.contains_key(name)
-        } else {
-            false
-        }    
+        self.enclosing.as_ref()?.borrow().get_at(depth - 1, slot)
     }
 
-    pub fn push_scope(&mut self) {
-        self.scopes.push(HashMap::new());
+    /// Stores a newly declared variable in this scope.
+    pub fn define(&mut self, slot: usize, value: LiteralValue) {
+        if slot >= self.slots.len() {
+            self.slots.resize(slot + 1, None);
+        }
+        self.slots[slot] = Some(value);
     }
 
-    pub fn pop_scope(&mut self) {
-        self.scopes.pop();
+    /// Assigns the slot `depth` scopes out. Returns false if it was never defined.
+    pub fn update_at(&mut self, depth: usize, slot: usize, value: LiteralValue) -> bool {
+        if depth == 0 {
+            return match self.slots.get_mut(slot) {
+                Some(Some(current)) => { *current = value; true }
+                _ => false,
+            };
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().update_at(depth - 1, slot, value),
+            None => false,
+        }
     }
 }
\ No newline at end of file
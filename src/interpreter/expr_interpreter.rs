@@ -1,98 +1,169 @@
+use std::rc::Rc;
+use crate::interpreter::function::Function;
 use crate::interpreter::Interpreter;
+use crate::interpreter::runtime_error::RuntimeError;
 use crate::parser::expr::{Expr, LiteralValue};
+use crate::resolver::Slot;
+use crate::scanner::token::Token;
 use crate::scanner::token_type::TokenType;
 
 impl Interpreter {
-    pub fn evaluate_expression(&mut self, expr: Expr) -> LiteralValue {
-        match expr {
+    pub(super) fn evaluate_expression(&mut self, expr: &Expr) -> Result<LiteralValue, RuntimeError> {
+        let value = match expr {
             Expr::Literal(value ) => { value.clone() }
-            Expr::Variable(var_token) => {
-                let name = var_token.get_lexeme().to_string();
-                self.environment.get(&name).expect(&format!("Undefined Variable: {:?}", var_token))
+            Expr::Variable { name, slot } => {
+                match self.look_up_variable(name, *slot) {
+                    Some(value) => value,
+                    None => return Err(undefined_variable(name)),
+                }
             }
 
-            Expr::Assign { identifier, value} => {
-                let identifier = identifier.get_lexeme().to_string();
-                let value = self.evaluate_expression(*value);
-                self.environment.update(identifier.clone(), value);
-                self.environment.get(&identifier).expect(&format!("Undefined variable '{}'", identifier))
+            Expr::Assign { identifier, value, slot } => {
+                let value = self.evaluate_expression(value)?;
+                if !self.assign_variable(identifier, *slot, value.clone()) {
+                    return Err(undefined_variable(identifier));
+                }
+                value
             }
 
             Expr::Unary {operator, right} => {
-                let value = self.evaluate_expression(*right);
-                match operator {
+                let value = self.evaluate_expression(right)?;
+                match operator.get_type() {
                     TokenType::Minus => {
                         match value {
                             LiteralValue::Number(n) => { LiteralValue::Number(-n) }
-                            _ => panic!("Unary '-' only works on numbers")
+                            _ => return Err(RuntimeError::new(operator.clone(), "Operand must be a number."))
                         }
                     }
                     TokenType::Bang => {
                         LiteralValue::Bool(!self.is_truthy(&value))
                     }
-                    _ => panic!("unexpected unary operator: {:?}", operator)
+                    _ => unreachable!("parser never builds unary {:?}", operator.get_type())
                 }
             }
             Expr::Binary { left, operator, right } => {
-                let left_val = self.evaluate_expression(*left);
-                let right_val = self.evaluate_expression(*right);
+                let left_val = self.evaluate_expression(left)?;
+                let right_val = self.evaluate_expression(right)?;
 
-                let result: LiteralValue = match operator {
+                let result: LiteralValue = match operator.get_type() {
                     TokenType::Plus => match (left_val, right_val) {
                         (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Number(a + b),
-                        _ => panic!("'+' only supported for numbers"),
+                        (LiteralValue::String(a), LiteralValue::String(b)) => LiteralValue::String(a + &b),
+                        _ => return Err(mismatched_operands(operator)),
                     },
                     TokenType::Minus => match (left_val, right_val) {
                         (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Number(a - b),
-                        _ => panic!("'-' only supported for numbers"),
+                        _ => return Err(numbers_expected(operator)),
                     },
                     TokenType::Star => match (left_val, right_val) {
                         (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Number(a * b),
-                        _ => panic!("'*' only supported for numbers"),
+                        _ => return Err(numbers_expected(operator)),
                     },
                     TokenType::Slash => match (left_val, right_val) {
                         (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Number(a / b),
-                        _ => panic!("'/' only supported for numbers"),
-                    },
-                    TokenType::EqualEqual => match (left_val, right_val) {
-                        (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Bool(a == b),
-                        (LiteralValue::Bool(a), LiteralValue::Bool(b)) => LiteralValue::Bool(a == b),
-                        (LiteralValue::Nil, LiteralValue::Nil) => LiteralValue::Bool(true),
-                        _ => LiteralValue::Bool(false),
-                    },
-
-                    TokenType::BangEqual => match (left_val, right_val) {
-                        (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Bool(a != b),
-                        (LiteralValue::Bool(a), LiteralValue::Bool(b)) => LiteralValue::Bool(a != b),
-                        (LiteralValue::Nil, LiteralValue::Nil) => LiteralValue::Bool(false),
-                        _ => LiteralValue::Bool(true),
+                        _ => return Err(numbers_expected(operator)),
                     },
+                    TokenType::EqualEqual => LiteralValue::Bool(is_equal(&left_val, &right_val)),
+                    TokenType::BangEqual => LiteralValue::Bool(!is_equal(&left_val, &right_val)),
                     TokenType::GreaterEqual => match (left_val, right_val) {
                         (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Bool(a >= b),
+                        (LiteralValue::String(a), LiteralValue::String(b)) => LiteralValue::Bool(a >= b),
                         (LiteralValue::Bool(a), LiteralValue::Bool(b)) => LiteralValue::Bool(a >= b),
-                        _ => LiteralValue::Bool(false),
+                        _ => return Err(mismatched_operands(operator)),
                     }
                     TokenType::Greater => match (left_val, right_val) {
                         (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Bool(a > b),
-                        (LiteralValue::Bool(a), LiteralValue::Bool(b)) => LiteralValue::Bool(a > b),
-                        _ => LiteralValue::Bool(false),
+                        (LiteralValue::String(a), LiteralValue::String(b)) => LiteralValue::Bool(a > b),
+                        (LiteralValue::Bool(a), LiteralValue::Bool(b)) => LiteralValue::Bool(a & !b),
+                        _ => return Err(mismatched_operands(operator)),
                     }
                     TokenType::LessEqual => match (left_val, right_val) {
                         (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Bool(a <= b),
+                        (LiteralValue::String(a), LiteralValue::String(b)) => LiteralValue::Bool(a <= b),
                         (LiteralValue::Bool(a), LiteralValue::Bool(b)) => LiteralValue::Bool(a <= b),
-                        _ => LiteralValue::Bool(false),
+                        _ => return Err(mismatched_operands(operator)),
                     }
                     TokenType::Less => match (left_val, right_val) {
                         (LiteralValue::Number(a), LiteralValue::Number(b)) => LiteralValue::Bool(a < b),
-                        (LiteralValue::Bool(a), LiteralValue::Bool(b)) => LiteralValue::Bool(a < b),
-                        _ => LiteralValue::Bool(false),
+                        (LiteralValue::String(a), LiteralValue::String(b)) => LiteralValue::Bool(a < b),
+                        (LiteralValue::Bool(a), LiteralValue::Bool(b)) => LiteralValue::Bool(!a & b),
+                        _ => return Err(mismatched_operands(operator)),
                     }
-                    _ => panic!("Unsupported binary operator"),
+                    _ => unreachable!("parser never builds binary {:?}", operator.get_type()),
                 };
                 result
             }
-            Expr::Grouping(inner) => {self.evaluate_expression(*inner)}
+            Expr::Logical { left, operator, right } => {
+                // the operand that decides the result is the result, e.g. nil or "x" gives "x"
+                let left_val = self.evaluate_expression(left)?;
+                let decided = match operator.get_type() {
+                    TokenType::Or => self.is_truthy(&left_val),
+                    TokenType::And => !self.is_truthy(&left_val),
+                    _ => unreachable!("parser never builds logical {:?}", operator.get_type())
+                };
+                if decided { left_val } else { self.evaluate_expression(right)? }
+            }
+            Expr::Call { callee, paren, arguments } => {
+                self.evaluate_call(callee, paren, arguments)?
+            }
+            Expr::Lambda { params, body, .. } => {
+                let function = Function::new(None, params.clone(), body.clone(), self.environment.clone());
+                LiteralValue::Function(Rc::new(function))
+            }
+            Expr::Grouping(inner) => {self.evaluate_expression(inner)?}
+            Expr::Interpolation { parts } => {
+                let mut text = String::new();
+                for part in parts {
+                    text.push_str(&self.evaluate_expression(part)?.to_string());
+                }
+                LiteralValue::String(text)
+            }
+        };
+        Ok(value)
+    }
+
+    /// Locals are read at the slot the resolver found; anything else is global.
+    fn look_up_variable(&self, name: &Token, slot: Option<Slot>) -> Option<LiteralValue> {
+        match slot {
+            Some(slot) => self.environment.borrow().get_at(slot.get_depth(), slot.get_index()),
+            None => self.globals.get(name.get_lexeme()).cloned(),
+        }
+    }
+
+    fn assign_variable(&mut self, name: &Token, slot: Option<Slot>, value: LiteralValue) -> bool {
+        match slot {
+            Some(slot) => self.environment.borrow_mut().update_at(slot.get_depth(), slot.get_index(), value),
+            None => match self.globals.get_mut(name.get_lexeme()) {
+                Some(current) => { *current = value; true }
+                None => false,
+            },
         }
+    }
+}
+
+fn undefined_variable(name: &Token) -> RuntimeError {
+    let message = format!("Undefined variable '{}'.", name.get_lexeme());
+    RuntimeError::new(name.clone(), &message)
+}
+
+fn numbers_expected(operator: &Token) -> RuntimeError {
+    RuntimeError::new(operator.clone(), "Operands must be numbers.")
+}
+
+/// For `+` and comparisons, which need two numbers or two strings.
+fn mismatched_operands(operator: &Token) -> RuntimeError {
+    RuntimeError::new(operator.clone(), "Operands must be two numbers or two strings.")
+}
 
+/// `==` never fails: values of different types are simply unequal.
+/// Strings compare by content, functions by identity.
+fn is_equal(left: &LiteralValue, right: &LiteralValue) -> bool {
+    match (left, right) {
+        (LiteralValue::Number(a), LiteralValue::Number(b)) => a == b,
+        (LiteralValue::String(a), LiteralValue::String(b)) => a == b,
+        (LiteralValue::Bool(a), LiteralValue::Bool(b)) => a == b,
+        (LiteralValue::Nil, LiteralValue::Nil) => true,
+        (LiteralValue::Function(a), LiteralValue::Function(b)) => Rc::ptr_eq(a, b),
+        _ => false,
     }
 }
\ No newline at end of file
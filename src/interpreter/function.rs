@@ -0,0 +1,39 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+use crate::interpreter::environment::Environment;
+use crate::parser::stmt::Stmt;
+use crate::scanner::token::Token;
+
+/// A function value, created when a `fun` declaration or lambda runs. It
+/// captures the scope it was declared in, so it can keep using that scope's
+/// variables. Lambdas have no name.
+/// Parameters and body are shared with the declaration, not copied.
+pub struct Function {
+    name: Option<Token>,
+    params: Rc<[Token]>,
+    body: Rc<[Stmt]>,
+    closure: Rc<RefCell<Environment>>,
+}
+
+impl Function {
+    pub fn new(name: Option<Token>, params: Rc<[Token]>, body: Rc<[Stmt]>, closure: Rc<RefCell<Environment>>) -> Function {
+        Function { name, params, body, closure }
+    }
+
+    pub fn get_name(&self) -> Option<&Token> { self.name.as_ref() }
+    pub fn get_params(&self) -> &[Token] { &self.params }
+    pub fn get_body(&self) -> &[Stmt] { &self.body }
+    pub fn get_closure(&self) -> &Rc<RefCell<Environment>> { &self.closure }
+    pub fn arity(&self) -> usize { self.params.len() }
+}
+
+impl Debug for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "<fn {}>", name.get_lexeme()),
+            None => write!(f, "<fn>"),
+        }
+    }
+}
\ No newline at end of file
@@ -1,22 +1,69 @@
 mod expr_interpreter;
-mod environment;
+pub mod environment;
 mod block_interpreter;
 mod stmt_interpreter;
+mod call_interpreter;
+mod control_flow;
+pub mod function;
+pub mod runtime_error;
 
-use environment::Environment;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::io::Write;
+use std::rc::Rc;
+use control_flow::ControlFlow;
+use environment::Environment;
+use function::Function;
+use runtime_error::RuntimeError;
 use crate::parser::expr::{Expr, LiteralValue};
 use crate::parser::stmt::Stmt;
+use crate::resolver::{ResolvedProgram, Slot};
 use crate::scanner::token::Token;
 
+/// Default for `Interpreter::with_max_call_depth`.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 200;
+
 pub struct Interpreter{
-    variables: Vec<Token>,
-    environment: Environment,
+    environment: Rc<RefCell<Environment>>,
+    globals: HashMap<String, LiteralValue>,
+    call_depth: usize,
+    max_call_depth: usize,
+    output: Box<dyn Write>, // where `print` writes
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
-        Interpreter{variables: vec![], environment:Environment::new()}
+        Interpreter{
+            environment: Rc::new(RefCell::new(Environment::new())),
+            globals: HashMap::new(),
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            output: Box::new(std::io::stdout()),
+        }
+    }
+
+    /// Sets how deeply script calls may nest before a call fails with a
+    /// "Stack overflow." runtime error. Calls run on the host's stack, so the
+    /// right limit depends on the stack size of the thread running the
+    /// interpreter and on whether it was built with optimizations. The default
+    /// fits the 8MB main thread of an unoptimized build; lower it for smaller
+    /// stacks, e.g. the 2MB of a `std::thread::spawn` thread, and raise it
+    /// for optimized builds, which use several times less stack per call.
+    pub fn with_max_call_depth(mut self, max_call_depth: usize) -> Interpreter {
+        self.max_call_depth = max_call_depth;
+        self
+    }
+
+    /// Sends the output of `print` somewhere other than stdout.
+    pub fn with_output(mut self, output: Box<dyn Write>) -> Interpreter {
+        self.output = output;
+        self
     }
 
     pub fn is_truthy(&self, value: &LiteralValue) -> bool {
@@ -25,44 +72,290 @@ impl Interpreter {
             LiteralValue::String(_) => {true}
             LiteralValue::Bool(n) => {n == &true}
             LiteralValue::Nil => {false}
+            LiteralValue::Function(_) => {true}
         }
     }
-    pub fn interpret(&mut self, stmts: Vec<Stmt>) {
-        for stmt in stmts {
-            self.evaluate(stmt);
-        }
-        println!("{:?}", self.environment)
+    /// Runs a program the `Resolver` has accepted.
+    /// Stops at the first runtime error; globals defined so far are kept, so
+    /// the interpreter can go on to run more code.
+    pub fn interpret(&mut self, program: &ResolvedProgram) -> Result<(), RuntimeError> {
+        self.evaluate_stmts(program.get_stmts())?;
+        Ok(())
     }
 
-    pub fn evaluate(&mut self, stmt: Stmt) {
+    fn evaluate(&mut self, stmt: &Stmt) -> Result<ControlFlow, RuntimeError> {
         match stmt {
             Stmt::Expression(expr) => {
-                self.evaluate_expression(expr);
+                self.evaluate_expression(expr)?;
+            }
+            Stmt::Print(expr) => {
+                let value = self.evaluate_expression(expr)?;
+                // as with `println!`, failing to write is the host's problem, not the script's
+                writeln!(self.output, "{}", value).expect("failed to write program output");
             }
             Stmt::Block{ stmts}  => {
-                self.evaluate_block(stmts);
+                return self.evaluate_block(stmts);
             }
-            Stmt::Var {name, initializer} => {
-                self.evaluate_var(name, initializer);
+            Stmt::Var {name, initializer, slot} => {
+                self.evaluate_var(name, initializer.as_ref(), *slot)?;
             }
             Stmt::If {condition, then_branch, else_branch} => {
-                self.evaluate_if_stmt(&condition, then_branch, else_branch);
+                return self.evaluate_if_stmt(condition, then_branch, else_branch.as_deref());
+            }
+            Stmt::While {condition, body, increment} => {
+                return self.evaluate_while_stmt(condition, body, increment.as_ref());
+            }
+            Stmt::For {..} => unreachable!("the resolver lowers `for` to `while`"),
+            Stmt::Function {name, params, body, slot} => {
+                let function = Function::new(Some(name.clone()), params.clone(), body.clone(), self.environment.clone());
+                self.define_variable(name, *slot, LiteralValue::Function(Rc::new(function)));
             }
-            _ => {}
+            Stmt::Return {value, ..} => {
+                let value = match value {
+                    Some(expr) => self.evaluate_expression(expr)?,
+                    None => LiteralValue::Nil,
+                };
+                return Ok(ControlFlow::Return(value));
+            }
+            Stmt::Break {..} => return Ok(ControlFlow::Break),
+            Stmt::Continue {..} => return Ok(ControlFlow::Continue),
         }
+        Ok(ControlFlow::Normal)
     }
 
-    fn evaluate_var(&mut self, name:Token, initializer:Option<Expr>) {
-        let lex = name.get_lexeme();
-        if(self.environment.contains_in_current_scope(&lex)){
-            panic!("Variable `{}` already defined in the current scope", lex);
-        }
+    fn evaluate_var(&mut self, name: &Token, initializer: Option<&Expr>, slot: Option<Slot>) -> Result<(), RuntimeError> {
         let val = match  initializer {
-            Some(expr) => self.evaluate_expression(expr),
+            Some(expr) => self.evaluate_expression(expr)?,
             _ => LiteralValue::Nil
 
         };
-        self.environment.set(name.get_lexeme().clone(),val)
+        self.define_variable(name, slot, val);
+        Ok(())
+    }
+
+    /// Declarations the resolver gave a slot are locals; the rest are globals.
+    fn define_variable(&mut self, name: &Token, slot: Option<Slot>, value: LiteralValue) {
+        match slot {
+            Some(slot) => self.environment.borrow_mut().define(slot.get_index(), value),
+            None => { self.globals.insert(name.get_lexeme().clone(), value); }
+        }
+
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::resolver::Resolver;
+    use crate::scanner::Scanner;
+
+    /// Collects what the interpreter prints.
+    #[derive(Clone, Default)]
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn resolve(source: &str) -> ResolvedProgram {
+        let mut scanner = Scanner::new(source.to_string());
+        let stmts = Parser::from_tokens(&mut scanner).parse_program().unwrap();
+        Resolver::new().resolve(stmts).unwrap()
+    }
+
+    /// Runs the source and returns its output, with the runtime error, if any,
+    /// as the last line.
+    fn run(source: &str) -> String {
+        run_with(Interpreter::new(), source)
+    }
+
+    fn run_with(interpreter: Interpreter, source: &str) -> String {
+        let program = resolve(source);
+        let output = Output::default();
+        let mut interpreter = interpreter.with_output(Box::new(output.clone()));
+        let result = interpreter.interpret(&program);
+
+        let mut text = String::from_utf8(output.0.borrow().clone()).unwrap();
+        if let Err(error) = result {
+            text.push_str(&format!("{}\n", error));
+        }
+        text
+    }
+
+    #[test]
+    fn shadowing_leaves_the_outer_variable_alone() {
+        assert_eq!(run("var a = 1; { var a = 2; a = 3; print a; } print a;"), "3\n1\n");
+        assert_eq!(run("{ var a = 1; { var a = 2; } a = a + 1; print a; }"), "2\n");
+    }
+
+    #[test]
+    fn locals_live_in_their_call() {
+        assert_eq!(
+            run("fun f(a, b) { var c = a + b; { var d = c * 2; return d; } } print f(1, 2); print f(3, 4);"),
+            "6\n14\n"
+        );
+        assert_eq!(run("fun fib(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } print fib(10);"), "55\n");
+    }
+
+    #[test]
+    fn a_skipped_declaration_leaves_its_slot_undefined() {
+        assert_eq!(
+            run("{ if (false) var x = 1; var y = 2; print x; }"),
+            "[line 1, column 42] Runtime error at 'x': Undefined variable 'x'.\n"
+        );
+    }
+
+    #[test]
+    fn runaway_recursion_is_a_runtime_error() {
+        // test threads have small stacks, so stay well inside them
+        let interpreter = Interpreter::new().with_max_call_depth(50);
+        assert_eq!(
+            run_with(interpreter, "fun f() { f(); }\nf();"),
+            "[line 1, column 13] Runtime error at ')': Stack overflow.\n"
+        );
+    }
+
+    #[test]
+    fn the_call_depth_limit_is_configurable() {
+        let source = "fun f(n) { if (n == 0) return 0; return 1 + f(n - 1); }";
+        let interpreter = Interpreter::new().with_max_call_depth(20);
+        assert_eq!(run_with(interpreter, &format!("{} print f(19);", source)), "19\n");
+        let interpreter = Interpreter::new().with_max_call_depth(20);
+        assert_eq!(
+            run_with(interpreter, &format!("{} print f(20);", source)),
+            "[line 1, column 52] Runtime error at ')': Stack overflow.\n"
+        );
+    }
+
+    #[test]
+    fn the_call_depth_recovers_after_a_stack_overflow() {
+        let mut interpreter = Interpreter::new().with_max_call_depth(10);
+        interpreter.interpret(&resolve("fun f() { f(); } fun g(n) { if (n > 0) g(n - 1); }")).unwrap();
 
+        let error = interpreter.interpret(&resolve("f();")).unwrap_err();
+        assert_eq!(error.get_message(), "Stack overflow.");
+        assert!(interpreter.interpret(&resolve("g(9);")).is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn closures_share_the_variables_they_capture() {
+        let source = "
+            var increment; var read;
+            fun make() {
+                var n = 0;
+                fun inc() { n = n + 1; }
+                fun get() { return n; }
+                increment = inc; read = get;
+            }
+            make(); increment(); increment(); print read();
+        ";
+        assert_eq!(run(source), "2\n");
+    }
+
+    #[test]
+    fn each_call_captures_a_fresh_scope() {
+        let source = "
+            fun counter() { var n = 0; return fun () { n = n + 1; return n; }; }
+            var a = counter(); var b = counter();
+            print a(); print a(); print b();
+        ";
+        assert_eq!(run(source), "1\n2\n1\n");
+    }
+
+    #[test]
+    fn closures_see_the_scope_they_were_defined_in() {
+        let source = "var a = \"global\"; { fun show() { print a; } show(); var a = \"block\"; show(); }";
+        assert_eq!(run(source), "global\nglobal\n");
+    }
+
+    #[test]
+    fn print_formats_each_kind_of_value() {
+        assert_eq!(run("print nil; print true; print 1.5; print 3; print \"s\";"), "nil\ntrue\n1.5\n3\ns\n");
+    }
+
+    #[test]
+    fn continue_in_a_for_loop_still_runs_the_increment() {
+        let source = "for (var i = 0; i < 5; i = i + 1) { if (i == 1) continue; if (i == 3) break; print i; }";
+        assert_eq!(run(source), "0\n2\n");
+    }
+
+    #[test]
+    fn break_and_continue_apply_to_the_innermost_loop() {
+        let source = "
+            var i = 0;
+            while (i < 3) {
+                i = i + 1;
+                if (i == 2) continue;
+                for (var j = 0; ; j = j + 1) { if (j == 2) break; print i * 10 + j; }
+            }
+        ";
+        assert_eq!(run(source), "10\n11\n30\n31\n");
+    }
+
+    #[test]
+    fn else_if_chains_take_the_first_true_branch() {
+        let source = "
+            fun grade(n) { if (n > 8) return \"a\"; else if (n > 5) return \"b\"; else return \"c\"; }
+            print grade(9); print grade(6); print grade(1);
+        ";
+        assert_eq!(run(source), "a\nb\nc\n");
+    }
+
+    #[test]
+    fn else_binds_to_the_nearest_if() {
+        assert_eq!(run("if (true) if (false) print 1; else print 2;"), "2\n");
+        assert_eq!(run("if (false) if (true) print 1; else print 2;"), "");
+    }
+
+    #[test]
+    fn strings_concatenate_and_compare() {
+        assert_eq!(run("var b = \"b\"; print \"a\" + b + \"c\";"), "abc\n");
+        assert_eq!(run("print \"ab\" == \"a\" + \"b\"; print \"a\" != \"a\"; print \"1\" == 1;"), "true\nfalse\nfalse\n");
+        assert_eq!(run("print \"a\" < \"b\"; print \"b\" <= \"a\"; print \"ab\" > \"a\"; print \"a\" >= \"a\";"), "true\nfalse\ntrue\ntrue\n");
+    }
+
+    #[test]
+    fn mixed_operands_are_runtime_errors() {
+        assert_eq!(
+            run("print \"a\" + 1;"),
+            "[line 1, column 11] Runtime error at '+': Operands must be two numbers or two strings.\n"
+        );
+        assert_eq!(
+            run("print 1 < \"a\";"),
+            "[line 1, column 9] Runtime error at '<': Operands must be two numbers or two strings.\n"
+        );
+        assert_eq!(run("print \"a\" - \"b\";"), "[line 1, column 11] Runtime error at '-': Operands must be numbers.\n");
+        assert_eq!(run("print -\"a\";"), "[line 1, column 7] Runtime error at '-': Operand must be a number.\n");
+    }
+
+    #[test]
+    fn runtime_errors_point_at_the_offending_token() {
+        assert_eq!(
+            run("print 1;\nvar s = \"x\";\n  print s();"),
+            "1\n[line 3, column 11] Runtime error at ')': Can only call functions, not 'x'.\n"
+        );
+        assert_eq!(
+            run("fun f(a) {}\nf(1,\n  2);"),
+            "[line 3, column 4] Runtime error at ')': Expected 1 arguments but got 2.\n"
+        );
+        assert_eq!(run("print y;"), "[line 1, column 7] Runtime error at 'y': Undefined variable 'y'.\n");
+    }
+
+    #[test]
+    fn interpolation_formats_each_embedded_value() {
+        assert_eq!(run("var n = 2; print \"${n} + 1 = ${n + 1}, ${nil} ${\"in\" + \"ner\"}\";"), "2 + 1 = 3, nil inner\n");
+    }
+
+    #[test]
+    fn nfc_equivalent_names_are_one_variable() {
+        assert_eq!(run("var caf\u{e9} = 1; cafe\u{301} = cafe\u{301} + 1; print caf\u{e9};"), "2\n");
+        assert_eq!(run("{ var caf\u{e9} = 1; print cafe\u{301}; }"), "1\n");
+    }
+}
@@ -0,0 +1,31 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use crate::scanner::token::Token;
+
+/// An error raised while the program runs, e.g. a type mismatch or an
+/// undefined variable, reported at the token whose evaluation failed.
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    token: Box<Token>, // boxed to keep the `Err` side of interpreter results small
+    message: String,
+}
+
+impl RuntimeError {
+    pub fn new(token: Token, message: &str) -> RuntimeError {
+        RuntimeError { token: Box::new(token), message: message.to_string() }
+    }
+
+    pub fn get_token(&self) -> &Token { &self.token }
+    pub fn get_message(&self) -> &str { &self.message }
+    pub fn get_line(&self) -> usize { self.token.get_line() }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[line {}, column {}] Runtime error at '{}': {}",
+            self.token.get_line(), self.token.get_column(), self.token.get_lexeme(), self.message
+        )
+    }
+}
\ No newline at end of file
@@ -1,37 +1,44 @@
-use std::cmp::PartialEq;
-use std::hint::black_box;
+use crate::interpreter::control_flow::ControlFlow;
 use crate::interpreter::Interpreter;
-use crate::parser::expr::{Expr, LiteralValue};
+use crate::interpreter::runtime_error::RuntimeError;
+use crate::parser::expr::Expr;
 use crate::parser::stmt::Stmt;
 
 
 impl Interpreter {
-    pub fn evaluate_if_stmt(&mut self, condition: &Expr, then_branch: Box<Stmt>, else_branch: Option<Box<Stmt>>) {
-        let cond = self.evaluate_expression(condition.clone());
+    pub(super) fn evaluate_if_stmt(&mut self, condition: &Expr, then_branch: &Stmt, else_branch: Option<&Stmt>) -> Result<ControlFlow, RuntimeError> {
+        let cond = self.evaluate_expression(condition)?;
 
-        if(self.is_truthy(&cond)){
-            let stmt = *then_branch;
-            match stmt {
-                Stmt::Block {stmts} => {
-                    self.evaluate_block(stmts);
-                }
-                _ => panic!("Expected block here"),
-            }
+        if self.is_truthy(&cond) {
+            self.evaluate(then_branch)
+        }
+        else if let Some(else_branch) = else_branch {
+            // an `else if` is just an `if` statement as the else branch
+            self.evaluate(else_branch)
         }
         else {
-            match else_branch{
-                Some(else_branch) =>
-                    {
-                        let stmt = *else_branch;
-                        match stmt {
-                            Stmt::Block {stmts} => {
-                                self.evaluate_block(stmts);
+            Ok(ControlFlow::Normal)
+        }
+    }
 
-                            }
-                            _ => panic!("Expected block here"),
-                        }                    },
-                _ => {}
+    /// Runs the body, then the increment, while the condition holds.
+    /// `break` and `continue` stop here; `return` keeps unwinding to the
+    /// function call.
+    pub(super) fn evaluate_while_stmt(&mut self, condition: &Expr, body: &Stmt, increment: Option<&Expr>) -> Result<ControlFlow, RuntimeError> {
+        loop {
+            let cond = self.evaluate_expression(condition)?;
+            if !self.is_truthy(&cond) {
+                break;
+            }
+            match self.evaluate(body)? {
+                ControlFlow::Break => break,
+                ControlFlow::Normal | ControlFlow::Continue => {}
+                flow @ ControlFlow::Return(_) => return Ok(flow),
+            }
+            if let Some(increment) = increment {
+                self.evaluate_expression(increment)?;
             }
         }
+        Ok(ControlFlow::Normal)
     }
 }
\ No newline at end of file
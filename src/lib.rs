@@ -0,0 +1,5 @@
+pub mod scanner;
+pub mod parser;
+pub mod lowering;
+pub mod interpreter;
+pub mod resolver;
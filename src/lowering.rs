@@ -0,0 +1,77 @@
+// Lowering pass, run by `Resolver::resolve` before it resolves scopes.
+//
+// Rewrites syntactic sugar into core statements, so the resolver, the
+// interpreter and any later backend only handle the core language:
+//
+// for ( init ; cond ; inc ) body  →  { init ; while ( cond ) body [inc] }
+//
+// A missing condition becomes `true`. The increment stays attached to the
+// `while` so that `continue` still runs it. Tokens, and so their spans, are
+// kept as they are, so errors still point at the original source.
+
+use std::rc::Rc;
+use crate::parser::expr::{Expr, LiteralValue};
+use crate::parser::stmt::Stmt;
+
+/// Lowers a whole program.
+pub fn lower(stmts: Vec<Stmt>) -> Vec<Stmt> {
+    stmts.into_iter().map(lower_stmt).collect()
+}
+
+pub fn lower_stmt(stmt: Stmt) -> Stmt {
+    match stmt {
+        Stmt::For { initializer, condition, increment, body } => {
+            let condition = condition.unwrap_or(Expr::Literal(LiteralValue::Bool(true)));
+            let body = Box::new(lower_stmt(*body));
+            let increment = increment.map(lower_expr);
+            let mut stmts = Vec::new();
+            if let Some(initializer) = initializer {
+                stmts.push(lower_stmt(*initializer));
+            }
+            stmts.push(Stmt::While { condition: lower_expr(condition), body, increment });
+            // the block scopes the loop variable to the loop
+            Stmt::Block { stmts }
+        }
+        Stmt::Expression(expr) => Stmt::Expression(lower_expr(expr)),
+        Stmt::Print(expr) => Stmt::Print(lower_expr(expr)),
+        Stmt::Var { name, initializer, slot } => Stmt::Var { name, initializer: initializer.map(lower_expr), slot },
+        Stmt::Block { stmts } => Stmt::Block { stmts: lower(stmts) },
+        Stmt::If { condition, then_branch, else_branch } => Stmt::If {
+            condition: lower_expr(condition),
+            then_branch: Box::new(lower_stmt(*then_branch)),
+            else_branch: else_branch.map(|stmt| Box::new(lower_stmt(*stmt))),
+        },
+        Stmt::While { condition, body, increment } => Stmt::While {
+            condition: lower_expr(condition),
+            body: Box::new(lower_stmt(*body)),
+            increment: increment.map(lower_expr),
+        },
+        Stmt::Function { name, params, body, slot } => Stmt::Function { name, params, body: lower_body(&body), slot },
+        Stmt::Return { keyword, value } => Stmt::Return { keyword, value: value.map(lower_expr) },
+        stmt @ (Stmt::Break { .. } | Stmt::Continue { .. }) => stmt,
+    }
+}
+
+/// Expressions have no sugar of their own yet, but lambda bodies are statements.
+pub fn lower_expr(expr: Expr) -> Expr {
+    let lower_box = |expr: Box<Expr>| Box::new(lower_expr(*expr));
+    match expr {
+        Expr::Lambda { keyword, params, body } => Expr::Lambda { keyword, params, body: lower_body(&body) },
+        Expr::Assign { identifier, value, slot } => Expr::Assign { identifier, value: lower_box(value), slot },
+        Expr::Unary { operator, right } => Expr::Unary { operator, right: lower_box(right) },
+        Expr::Binary { left, operator, right } => Expr::Binary { left: lower_box(left), operator, right: lower_box(right) },
+        Expr::Logical { left, operator, right } => Expr::Logical { left: lower_box(left), operator, right: lower_box(right) },
+        Expr::Call { callee, paren, arguments } => Expr::Call {
+            callee: lower_box(callee),
+            paren,
+            arguments: arguments.into_iter().map(lower_expr).collect(),
+        },
+        Expr::Grouping(inner) => Expr::Grouping(lower_box(inner)),
+        Expr::Interpolation { parts } => Expr::Interpolation { parts: parts.into_iter().map(lower_expr).collect() },
+        expr @ (Expr::Literal(_) | Expr::Variable { .. }) => expr,
+    }
+}
+
+fn lower_body(body: &[Stmt]) -> Rc<[Stmt]> {
+    body.iter().cloned().map(lower_stmt).collect()
+}
\ No newline at end of file
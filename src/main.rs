@@ -1,10 +1,9 @@
-use crate::interpreter::Interpreter;
-use crate::parser::Parser;
-use crate::scanner::Scanner;
-
-mod scanner;
-mod parser;
-mod interpreter;
+use dia::interpreter::Interpreter;
+use dia::parser::Parser;
+use dia::parser::ast_printer::AstPrinter;
+use dia::resolver::Resolver;
+use dia::scanner::Scanner;
+use dia::scanner::source_map::SourceMap;
 
 fn main() {
     let source = String::from("
@@ -18,20 +17,48 @@ fn main() {
           x=0;
         }
         x =  x + y;
+        print x;
     ");
-    let mut scanner = Scanner::new(source);
-    scanner.scan_tokens();
+    let mut source_map = SourceMap::new();
+    let file = source_map.add_file("<main>".to_string(), source);
 
-    let tokens = scanner.get_tokens();
-    dbg!(&tokens);
-    let mut parser = Parser::new(tokens.clone());
+    // Step 1 + 2: Scan and parse in one pass, tokens are lexed on demand
+    let mut scanner = Scanner::for_file(&source_map, file);
+    let parsed = Parser::from_tokens(&mut scanner).parse_program();
 
-    // Step 3: Parse expression
-    let expression_ast= parser.parse_program();
+    // Lexical errors usually cause the syntax errors, so they come first
+    if !scanner.get_errors().is_empty() {
+        for error in scanner.get_errors() {
+            eprintln!("{}", error);
+        }
+        return;
+    }
+    let expression_ast = match parsed {
+        Ok(stmts) => stmts,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            return;
+        }
+    };
+
+    // Step 3: Desugar `for` loops, resolve variable scopes and report static errors
+    let program = match Resolver::new().resolve(expression_ast) {
+        Ok(program) => program,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            return;
+        }
+    };
 
-    // Step 4: Debug print AST
-    println!("{:#?}", expression_ast);
+    // Step 4: Print the lowered AST as S-expressions
+    println!("{}", AstPrinter::new().print_program(program.get_stmts()));
 
     let mut interpreter = Interpreter::new();
-    interpreter.interpret(expression_ast);
+    if let Err(error) = interpreter.interpret(&program) {
+        eprintln!("{}", error);
+    }
 }
@@ -0,0 +1,166 @@
+use crate::parser::expr::{Expr, LiteralValue};
+use crate::parser::stmt::Stmt;
+use crate::scanner::token::Token;
+
+/// Prints the AST as compact S-expressions, e.g. `(+ 1 (* 2 3))`, one line
+/// per top-level statement. Missing optional parts print as `_`.
+#[derive(Debug, Default)]
+pub struct AstPrinter;
+
+impl AstPrinter {
+    pub fn new() -> AstPrinter {
+        AstPrinter
+    }
+
+    pub fn print_program(&self, stmts: &[Stmt]) -> String {
+        stmts.iter()
+            .map(|stmt| self.print_stmt(stmt))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn print_stmt(&self, stmt: &Stmt) -> String {
+        match stmt {
+            Stmt::Expression(expr) => parenthesize(";", &[self.print_expr(expr)]),
+            Stmt::Print(expr) => parenthesize("print", &[self.print_expr(expr)]),
+            Stmt::Var { name, initializer, .. } => match initializer {
+                Some(initializer) => parenthesize("var", &[lexeme(name), self.print_expr(initializer)]),
+                None => parenthesize("var", &[lexeme(name)]),
+            },
+            Stmt::Block { stmts } => parenthesize("block", &self.print_stmts(stmts)),
+            Stmt::If { condition, then_branch, else_branch } => {
+                let mut parts = vec![self.print_expr(condition), self.print_stmt(then_branch)];
+                if let Some(else_branch) = else_branch {
+                    parts.push(self.print_stmt(else_branch));
+                }
+                parenthesize("if", &parts)
+            }
+            Stmt::While { condition, body, increment } => {
+                let mut parts = vec![self.print_expr(condition), self.print_stmt(body)];
+                if let Some(increment) = increment {
+                    parts.push(self.print_expr(increment));
+                }
+                parenthesize("while", &parts)
+            }
+            Stmt::For { initializer, condition, increment, body } => parenthesize("for", &[
+                initializer.as_ref().map_or("_".to_string(), |stmt| self.print_stmt(stmt)),
+                condition.as_ref().map_or("_".to_string(), |expr| self.print_expr(expr)),
+                increment.as_ref().map_or("_".to_string(), |expr| self.print_expr(expr)),
+                self.print_stmt(body),
+            ]),
+            Stmt::Function { name, params, body, .. } => {
+                let mut parts = vec![lexeme(name)];
+                parts.extend(self.print_function(params, body));
+                parenthesize("fun", &parts)
+            }
+            Stmt::Return { value, .. } => match value {
+                Some(value) => parenthesize("return", &[self.print_expr(value)]),
+                None => parenthesize("return", &[]),
+            },
+            Stmt::Break { .. } => parenthesize("break", &[]),
+            Stmt::Continue { .. } => parenthesize("continue", &[]),
+        }
+    }
+
+    pub fn print_expr(&self, expr: &Expr) -> String {
+        match expr {
+            Expr::Literal(value) => match value {
+                LiteralValue::String(text) => format!("{:?}", text),
+                value => value.to_string(),
+            },
+            Expr::Variable { name, .. } => lexeme(name),
+            Expr::Assign { identifier, value, .. } => parenthesize("=", &[lexeme(identifier), self.print_expr(value)]),
+            Expr::Unary { operator, right } => parenthesize(operator.get_lexeme(), &[self.print_expr(right)]),
+            Expr::Binary { left, operator, right } | Expr::Logical { left, operator, right } => {
+                parenthesize(operator.get_lexeme(), &[self.print_expr(left), self.print_expr(right)])
+            }
+            Expr::Call { callee, arguments, .. } => {
+                let mut parts = vec![self.print_expr(callee)];
+                parts.extend(arguments.iter().map(|argument| self.print_expr(argument)));
+                parenthesize("call", &parts)
+            }
+            Expr::Lambda { params, body, .. } => parenthesize("fun", &self.print_function(params, body)),
+            Expr::Grouping(inner) => parenthesize("group", &[self.print_expr(inner)]),
+            Expr::Interpolation { parts } => {
+                let parts: Vec<String> = parts.iter().map(|part| self.print_expr(part)).collect();
+                parenthesize("str", &parts)
+            }
+        }
+    }
+
+    fn print_stmts(&self, stmts: &[Stmt]) -> Vec<String> {
+        stmts.iter().map(|stmt| self.print_stmt(stmt)).collect()
+    }
+
+    /// `(a b) stmt...`, the body's statements inline rather than as a block.
+    fn print_function(&self, params: &[Token], body: &[Stmt]) -> Vec<String> {
+        let params: Vec<String> = params.iter().map(lexeme).collect();
+        let mut parts = vec![format!("({})", params.join(" "))];
+        parts.extend(self.print_stmts(body));
+        parts
+    }
+}
+
+fn parenthesize(name: &str, parts: &[String]) -> String {
+    if parts.is_empty() {
+        return format!("({})", name);
+    }
+    format!("({} {})", name, parts.join(" "))
+}
+
+fn lexeme(token: &Token) -> String {
+    token.get_lexeme().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lowering;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    fn print(source: &str) -> String {
+        let mut scanner = Scanner::new(source.to_string());
+        let stmts = Parser::from_tokens(&mut scanner).parse_program().unwrap();
+        AstPrinter::new().print_program(&stmts)
+    }
+
+    #[test]
+    fn binary_operators_nest_by_precedence() {
+        assert_eq!(print("1 + 2 * 3 - 4 / 5;"), "(; (- (+ 1 (* 2 3)) (/ 4 5)))");
+        assert_eq!(print("1 < 2 == 3 >= 4;"), "(; (== (< 1 2) (>= 3 4)))");
+        assert_eq!(print("a or b and c == d;"), "(; (or a (and b (== c d))))");
+    }
+
+    #[test]
+    fn unary_call_and_grouping_bind_tightest() {
+        assert_eq!(print("-f(1)(2) * !(a + b);"), "(; (* (- (call (call f 1) 2)) (! (group (+ a b)))))");
+    }
+
+    #[test]
+    fn assignment_is_right_associative() {
+        assert_eq!(print("a = b = 1 or 2;"), "(; (= a (= b (or 1 2))))");
+    }
+
+    #[test]
+    fn literals_and_statements() {
+        assert_eq!(print("print nil; var s = \"a\\\"b\";"), "(print nil)\n(var s \"a\\\"b\")");
+        assert_eq!(
+            print("fun f(a, b) { if (a) return b; else return; }"),
+            "(fun f (a b) (if a (return b) (return)))"
+        );
+    }
+
+    #[test]
+    fn lowered_for_prints_as_while() {
+        let source = "for (var i = 0; i < 3; i = i + 1) print i;";
+        assert_eq!(print(source), "(for (var i 0) (< i 3) (= i (+ i 1)) (print i))");
+
+        let mut scanner = Scanner::new(source.to_string());
+        let stmts = lowering::lower(Parser::from_tokens(&mut scanner).parse_program().unwrap());
+        assert_eq!(
+            AstPrinter::new().print_program(&stmts),
+            "(block (var i 0) (while (< i 3) (print i) (= i (+ i 1))))"
+        );
+    }
+}
@@ -2,17 +2,28 @@
 // Expression Grammar (BNF-style)
 //
 // expression     → assignment ;
-// assignment     → equality | IDENTIFIER "=" assignment
+// assignment     → IDENTIFIER "=" assignment | logic_or ;
+// logic_or       → logic_and ( "or" logic_and )* ;
+// logic_and      → equality ( "and" equality )* ;
 // equality       → comparison ( ( "!=" | "==" ) comparison )* ;
 // comparison     → term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
 // term           → factor ( ( "-" | "+" ) factor )* ;
 // factor         → unary ( ( "/" | "*" ) unary )* ;
-// unary          → ( "!" | "-" ) unary | primary ;
-// primary        → NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")" ;
+// unary          → ( "!" | "-" ) unary | call ;
+// call           → primary ( "(" arguments? ")" )* ;
+// arguments      → expression ( "," expression )* ;
+// primary        → NUMBER | STRING | interpolation | "true" | "false" | "nil" | "(" expression ")" | lambda ;
+// lambda         → "fun" "(" parameters? ")" block ;
+// interpolation  → ( INTERPOLATION expression )+ STRING ;
 // ==========================================
 
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use crate::interpreter::function::Function;
+use crate::parser::stmt::Stmt;
+use crate::resolver::Slot;
 use crate::scanner::token::Token;
-use crate::scanner::token_type::TokenType;
 
 #[derive(Debug, Clone)]
 pub enum LiteralValue {
@@ -20,6 +31,19 @@ pub enum LiteralValue {
     String(String),    // String literals, e.g., "hello"
     Bool(bool),        // true or false
     Nil,               // Represents absence of a value
+    Function(Rc<Function>), // Function values, only created at runtime
+}
+
+impl Display for LiteralValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LiteralValue::Number(n) => write!(f, "{}", n),
+            LiteralValue::String(s) => write!(f, "{}", s),
+            LiteralValue::Bool(b) => write!(f, "{}", b),
+            LiteralValue::Nil => write!(f, "nil"),
+            LiteralValue::Function(function) => write!(f, "{:?}", function),
+        }
+    }
 }
 
 /// AST node representing an expression
@@ -27,24 +51,59 @@ pub enum LiteralValue {
 pub enum Expr {
     /// A literal value
     Literal(LiteralValue),
-    Variable(Token),
+    /// Variable read, e.g., x
+    Variable {
+        name: Token,
+        slot: Option<Slot>,
+    },
+    /// Assignment, e.g., x = 1
     Assign{
         identifier: Token,
         value: Box<Expr>,
+        slot: Option<Slot>,
     },
     /// Unary operator expression, e.g., -x or !flag
     Unary {
-        operator: TokenType,
+        operator: Token,
         right: Box<Expr>,   // Expression the operator applies to
     },
 
     /// Binary operator expression, e.g., x + y, a * b
     Binary {
         left: Box<Expr>,    // Left-hand side expression
-        operator: TokenType,
+        operator: Token,
         right: Box<Expr>,   // Right-hand side expression
     },
 
+    /// Short-circuiting `and` / `or`, e.g., a or b
+    /// Kept apart from Binary since the right side may never be evaluated.
+    Logical {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>,
+    },
+
+    /// Function call, e.g., add(1, 2)
+    Call {
+        callee: Box<Expr>,
+        paren: Token,       // closing ')', where call errors are reported
+        arguments: Vec<Expr>,
+    },
+
+    /// Anonymous function, e.g., fun (x) { return x * 2; }
+    /// Parameters and body are shared with the function values created from it.
+    Lambda {
+        keyword: Token,     // the `fun` token, for error positions
+        params: Rc<[Token]>,
+        body: Rc<[Stmt]>,
+    },
+
     /// Grouped expression, e.g., (a + b)
     Grouping(Box<Expr>),
+
+    /// String with embedded expressions, e.g., "Hello ${name}!"
+    /// Text segments appear as string literals between the expressions.
+    Interpolation {
+        parts: Vec<Expr>,
+    },
 }
@@ -1,28 +1,71 @@
 use crate::parser::expr::{Expr, LiteralValue};
 use crate::parser::Parser;
+use crate::parser::parse_error::ParseError;
 use crate::scanner::token_type::TokenType;
 
-impl Parser {
-    pub fn parse_expression(&mut self) -> Expr {
+impl Parser<'_> {
+    pub fn parse_expression(&mut self) -> Result<Expr, ParseError> {
         self.parse_assignment()
     }
-    fn parse_assignment(&mut self) -> Expr {
-        let expr = self.parse_equality();
-        let ident = self.previous().clone();
-        if(self.match_token(&[TokenType::Equal])){
-            let value = self.parse_expression();
-            return Expr::Assign {identifier: ident, value: Box::new(value), }
+    /// assignment → IDENTIFIER "=" assignment | logic_or
+    /// The target is parsed as an ordinary expression first, then checked to
+    /// be something assignable (only variables for now).
+    fn parse_assignment(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.parse_or()?;
+        if self.match_token(&[TokenType::Equal]) {
+            let equals = self.previous().clone();
+            let value = self.parse_assignment()?;
 
+            if let Expr::Variable { name, .. } = expr {
+                return Ok(Expr::Assign {identifier: name, value: Box::new(value), slot: None });
+            }
+            // the parser is not confused, so report without unwinding
+            let error = self.error(&equals, "Invalid assignment target.");
+            self.errors.push(error);
         }
-        expr
+        Ok(expr)
     }
 
-    pub fn parse_equality(&mut self) -> Expr {
-        let mut expr = self.parse_comparison();
+    /// logic_or → logic_and ("or" logic_and)*
+    pub fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_and()?;
+
+        while self.match_token(&[TokenType::Or]) {
+            let operator = self.previous().clone();
+            let right = self.parse_and()?;
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    /// logic_and → equality ("and" equality)*
+    pub fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_equality()?;
+
+        while self.match_token(&[TokenType::And]) {
+            let operator = self.previous().clone();
+            let right = self.parse_equality()?;
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    pub fn parse_equality(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_comparison()?;
 
         while self.match_token(&[TokenType::BangEqual, TokenType::EqualEqual]) {
-            let operator = self.previous().get_type();
-            let right = self.parse_comparison();
+            let operator = self.previous().clone();
+            let right = self.parse_comparison()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
                 operator,
@@ -30,12 +73,12 @@ impl Parser {
             };
         }
 
-        expr
+        Ok(expr)
     }
 
     /// comparison → term ((">" | ">=" | "<" | "<=") term)*
-    pub fn parse_comparison(&mut self) -> Expr {
-        let mut expr = self.parse_term();
+    pub fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_term()?;
 
         while self.match_token(&[
             TokenType::Greater,
@@ -43,8 +86,8 @@ impl Parser {
             TokenType::Less,
             TokenType::LessEqual,
         ]) {
-            let operator = self.previous().get_type();
-            let right = self.parse_term();
+            let operator = self.previous().clone();
+            let right = self.parse_term()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
                 operator,
@@ -52,16 +95,16 @@ impl Parser {
             };
         }
 
-        expr
+        Ok(expr)
     }
 
     /// term → factor (("+" | "-") factor)*
-    pub fn parse_term(&mut self) -> Expr {
-        let mut expr = self.parse_factor();
+    pub fn parse_term(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_factor()?;
 
         while self.match_token(&[TokenType::Plus, TokenType::Minus]) {
-            let operator = self.previous().get_type();
-            let right = self.parse_factor();
+            let operator = self.previous().clone();
+            let right = self.parse_factor()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
                 operator,
@@ -69,16 +112,16 @@ impl Parser {
             };
         }
 
-        expr
+        Ok(expr)
     }
 
     /// factor → unary (("*" | "/") unary)*
-    pub fn parse_factor(&mut self) -> Expr {
-        let mut expr = self.parse_unary();
+    pub fn parse_factor(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_unary()?;
 
         while self.match_token(&[TokenType::Star, TokenType::Slash]) {
-            let operator = self.previous().get_type();
-            let right = self.parse_unary();
+            let operator = self.previous().clone();
+            let right = self.parse_unary()?;
             expr = Expr::Binary {
                 left: Box::new(expr),
                 operator,
@@ -86,55 +129,155 @@ impl Parser {
             };
         }
 
-        expr
+        Ok(expr)
     }
 
-    /// unary → ("!" | "-") unary | primary
-    pub fn parse_unary(&mut self) -> Expr {
+    /// unary → ("!" | "-") unary | call
+    pub fn parse_unary(&mut self) -> Result<Expr, ParseError> {
         if self.match_token(&[TokenType::Bang, TokenType::Minus]) {
-            let operator = self.previous().get_type();
-            let right = self.parse_unary();
-            return Expr::Unary {
+            let operator = self.previous().clone();
+            let right = self.parse_unary()?;
+            return Ok(Expr::Unary {
                 operator,
                 right: Box::new(right),
-            };
+            });
+        }
+
+        self.parse_call()
+    }
+
+    /// call → primary ( "(" arguments? ")" )*
+    pub fn parse_call(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_primary()?;
+
+        while self.match_token(&[TokenType::LeftParen]) {
+            expr = self.finish_call(expr)?;
+        }
+
+        Ok(expr)
+    }
+
+    /// arguments → expression ( "," expression )*
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let mut arguments = Vec::new();
+        if !self.check(TokenType::RightParen) {
+            loop {
+                arguments.push(self.parse_expression()?);
+                if !self.match_token(&[TokenType::Comma]) {
+                    break;
+                }
+            }
         }
 
-        self.parse_primary()
+        let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments.")?.clone();
+        Ok(Expr::Call { callee: Box::new(callee), paren, arguments })
     }
 
-    /// primary → NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")"
-    pub fn parse_primary(&mut self) -> Expr {
+    /// primary → NUMBER | STRING | "true" | "false" | "nil" | "(" expression ")" | lambda
+    pub fn parse_primary(&mut self) -> Result<Expr, ParseError> {
         if self.match_token(&[TokenType::False]) {
-            return Expr::Literal(LiteralValue::Bool(false));
+            return Ok(Expr::Literal(LiteralValue::Bool(false)));
         }
 
         if self.match_token(&[TokenType::True]) {
-            return Expr::Literal(LiteralValue::Bool(true));
+            return Ok(Expr::Literal(LiteralValue::Bool(true)));
         }
 
         if self.match_token(&[TokenType::Nil]) {
-            return Expr::Literal(LiteralValue::Nil);
+            return Ok(Expr::Literal(LiteralValue::Nil));
         }
 
         if self.match_token(&[TokenType::Number]) {
-            let value: f64 = self.previous().get_lexeme().parse().unwrap();
-            return Expr::Literal(LiteralValue::Number(value));
+            let token = self.previous().clone();
+            let value = match number_value(token.get_lexeme()) {
+                Some(value) => value,
+                None => {
+                    // the rest still parses fine, so report without unwinding
+                    let error = self.error(&token, "Number literal is out of range.");
+                    self.errors.push(error);
+                    0.0
+                }
+            };
+            return Ok(Expr::Literal(LiteralValue::Number(value)));
         }
 
         if self.match_token(&[TokenType::String]) {
-            return Expr::Literal(LiteralValue::String(self.previous().get_lexeme().clone()));
+            return Ok(Expr::Literal(LiteralValue::String(self.previous().get_lexeme().clone())));
+        }
+
+        if self.match_token(&[TokenType::Interpolation]) {
+            return self.parse_interpolation();
         }
 
         if self.match_token(&[TokenType::LeftParen]) {
-            let expr = self.parse_expression();
-            self.consume(TokenType::RightParen, "Expect ')' after expression.");
-            return Expr::Grouping(Box::new(expr));
+            let expr = self.parse_expression()?;
+            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+            return Ok(Expr::Grouping(Box::new(expr)));
         }
         if self.match_token(&[TokenType::Identifier]) {
-            return Expr::Variable(self.previous().clone());
+            return Ok(Expr::Variable { name: self.previous().clone(), slot: None });
+        }
+        if self.match_token(&[TokenType::Fun]) {
+            return self.parse_lambda();
         }
-        self.error(self.peek(), "Expected expression.");
-        Expr::Literal(LiteralValue::Nil)
+        Err(self.error(self.peek(), "Expected expression."))
+    }
+
+    /// lambda → "fun" "(" parameters? ")" block
+    fn parse_lambda(&mut self) -> Result<Expr, ParseError> {
+        let keyword = self.previous().clone();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'fun'.")?;
+        let (params, body) = self.function_rest()?;
+        Ok(Expr::Lambda { keyword, params: params.into(), body: body.into() })
+    }
+
+    /// interpolation → ( INTERPOLATION expression )+ STRING
+    fn parse_interpolation(&mut self) -> Result<Expr, ParseError> {
+        let mut parts = Vec::new();
+        loop {
+            let text = self.previous().get_lexeme().clone();
+            if !text.is_empty() {
+                parts.push(Expr::Literal(LiteralValue::String(text)));
+            }
+            parts.push(self.parse_expression()?);
+
+            if !self.match_token(&[TokenType::Interpolation]) {
+                break;
+            }
+        }
+
+        let text = self.consume(TokenType::String, "Expect '}' after interpolated expression.")?.get_lexeme().clone();
+        if !text.is_empty() {
+            parts.push(Expr::Literal(LiteralValue::String(text)));
+        }
+        Ok(Expr::Interpolation { parts })
+    }
+}
+
+/// Converts a NUMBER lexeme, already validated by the scanner, to its value.
+/// Returns `None` when a hex, binary or octal literal does not fit in 64 bits.
+fn number_value(lexeme: &str) -> Option<f64> {
+    let digits = lexeme.replace('_', "");
+    let radix = match digits.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0b" | "0B") => 2,
+        Some("0o" | "0O") => 8,
+        _ => return digits.parse().ok(),
+    };
+    u64::from_str_radix(&digits[2..], radix).ok().map(|value| value as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_values() {
+        assert_eq!(number_value("0x1F"), Some(31.0));
+        assert_eq!(number_value("0b1010"), Some(10.0));
+        assert_eq!(number_value("0o17"), Some(15.0));
+        assert_eq!(number_value("1_000_000"), Some(1_000_000.0));
+        assert_eq!(number_value("2.5E-3"), Some(0.0025));
+        assert_eq!(number_value("0xFFFF_FFFF_FFFF_FFFF_F"), None);
     }
 }
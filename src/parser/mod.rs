@@ -2,39 +2,73 @@ pub mod expr;
 mod expression_parser;
 pub mod stmt;
 mod stmt_parser;
+pub mod parse_error;
+pub mod ast_printer;
 
+use crate::parser::parse_error::ParseError;
+use crate::scanner::source_map::{FileId, Span};
 use crate::scanner::token::Token;
 use crate::scanner::token_type::TokenType;
 
-pub struct Parser {
-    tokens: Vec<Token>,
-    current: usize,
+/// Pulls tokens from its source on demand and only keeps the previous,
+/// current and next token, so memory stays flat however long the input is.
+pub struct Parser<'a> {
+    tokens: Box<dyn Iterator<Item = Token> + 'a>,
+    previous: Option<Token>,
+    current: Token,
+    next: Token,
+    errors: Vec<ParseError>,
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Parser { Parser { tokens: tokens.clone(), current: 0, } }
+impl<'a> Parser<'a> {
+    pub fn new(tokens: Vec<Token>) -> Parser<'a> { Parser::from_tokens(tokens.into_iter()) }
 
+    /// Parses straight from a token stream, e.g. a `Scanner` (or `&mut Scanner`
+    /// to inspect its errors afterwards), without collecting the tokens first.
+    pub fn from_tokens(tokens: impl Iterator<Item = Token> + 'a) -> Parser<'a> {
+        let mut tokens: Box<dyn Iterator<Item = Token> + 'a> = Box::new(tokens);
+        let current = tokens.next().unwrap_or_else(|| {
+            Token::new(TokenType::Eof, "".to_string(), Span::new(FileId::default(), 0, 0), 1, 1)
+        });
+        let next = Parser::pull(&mut tokens, &current);
+        Parser { tokens, previous: None, current, next, errors: vec![] }
+    }
+
+    /// The token after `last`, or another `Eof` once the stream has ended.
+    fn pull(tokens: &mut Box<dyn Iterator<Item = Token> + 'a>, last: &Token) -> Token {
+        if last.get_type() != TokenType::Eof
+            && let Some(token) = tokens.next() {
+            return token;
+        }
+        let end = Span::new(last.get_span().get_file(), last.get_end(), last.get_end());
+        Token::new(TokenType::Eof, "".to_string(), end, last.get_line(), last.get_column())
+    }
+}
+
+impl Parser<'_> {
     pub fn is_eof(&self) -> bool {
         self.peek().get_type() == TokenType::Eof
     }
 
     pub fn advance(&mut self) -> &Token {
-        if(!self.is_eof()) {
-            self.current += 1;
+        if !self.is_eof() {
+            let next = Parser::pull(&mut self.tokens, &self.next);
+            let current = std::mem::replace(&mut self.next, next);
+            self.previous = Some(std::mem::replace(&mut self.current, current));
         }
         self.previous()
     }
 
-    pub fn previous(&mut self) -> &Token {
-        self.tokens.get(self.current - 1).unwrap()
+    pub fn previous(&self) -> &Token {
+        self.previous.as_ref().unwrap()
     }
 
     pub fn peek (&self) -> &Token {
-        self.tokens.get(self.current).unwrap()
+        &self.current
     }
 
     pub fn peek_next(&self) -> &Token {
-        self.tokens.get(self.current + 1).unwrap_or(self.peek())
+        &self.next
     }
 
     pub fn check(&self, expected: TokenType) -> bool {
@@ -43,7 +77,7 @@ impl Parser {
 
     pub fn match_token(&mut self, types: &[TokenType]) -> bool {
         for t in types {
-            if(self.check(t.clone())){
+            if self.check(t.clone()) {
                 self.advance();
                 return true;
             }
@@ -51,19 +85,97 @@ impl Parser {
         false
     }
 
-    pub fn error(&self, token: &Token, message: &str) {
-        eprintln!(
-            "[line {}] Syntax error at '{}': {}",
-            token.get_line(), token.get_lexeme(), message
-        );
+    pub fn error(&self, token: &Token, message: &str) -> ParseError {
+        ParseError::new(token.clone(), message)
     }
 
     // Consume expected token or report error
-    pub fn consume(&mut self, expected: TokenType, message: &str) -> &Token {
+    pub fn consume(&mut self, expected: TokenType, message: &str) -> Result<&Token, ParseError> {
         if self.check(expected) {
-            return self.advance();
+            return Ok(self.advance());
         }
-        self.error(self.peek(), message);
-        self.peek() // return current token so parsing can continue
+        Err(self.error(self.peek(), message))
     }
-}
\ No newline at end of file
+
+    /// After a syntax error, skips tokens up to the next likely statement
+    /// boundary (just after a `;`, or before a statement keyword or a `}`) so
+    /// that parsing can resume and report further real errors. The `}` is
+    /// left for the enclosing block to close.
+    fn synchronize(&mut self) {
+        if !self.at_boundary() {
+            self.advance();
+        }
+        while !self.is_eof() && !self.at_boundary() {
+            if self.previous().get_type() == TokenType::Semicolon {
+                return;
+            }
+            self.advance();
+        }
+    }
+
+    /// Keywords that `declaration` always consumes, and `}`, which a block
+    /// or `parse_program` consumes, so resuming in front of one is
+    /// guaranteed to make progress.
+    fn at_boundary(&self) -> bool {
+        matches!(
+            self.peek().get_type(),
+            TokenType::Fun | TokenType::Var | TokenType::For | TokenType::If | TokenType::While
+                | TokenType::Print | TokenType::Return | TokenType::Break | TokenType::Continue
+                | TokenType::RightBrace
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast_printer::AstPrinter;
+    use crate::scanner::Scanner;
+
+    fn errors(source: &str) -> Vec<String> {
+        match Parser::from_tokens(Scanner::new(source.to_string())).parse_program() {
+            Ok(_) => vec![],
+            Err(errors) => errors.iter().map(|error| error.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parses_straight_from_a_scanner() {
+        let mut scanner = Scanner::new("print 1 + 2;\nprint @;".to_string());
+        let errors = Parser::from_tokens(&mut scanner).parse_program().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(scanner.get_errors()[0].to_string(), "[line 2, column 7] Scan error: Unexpected character '@'");
+
+        let stmts = Parser::from_tokens(Scanner::new("print 1 + 2;".to_string())).parse_program().unwrap();
+        assert_eq!(AstPrinter::new().print_program(&stmts), "(print (+ 1 2))");
+    }
+
+    #[test]
+    fn reports_every_error_after_resynchronising() {
+        let source = "var = 1;\nprint 2\nvar b = ;\n{ var c = 1 }\nprint 3;\nif (true) { print ; }\n}\nprint 4";
+        assert_eq!(
+            errors(source),
+            vec![
+                "[line 1, column 5] Syntax error at '=': Expect variable name.",
+                "[line 3, column 1] Syntax error at 'var': Expect ';' after value.",
+                "[line 3, column 9] Syntax error at ';': Expected expression.",
+                "[line 4, column 13] Syntax error at '}': Expect ';' after variable declaration.",
+                "[line 6, column 19] Syntax error at ';': Expected expression.",
+                "[line 7, column 1] Syntax error at '}': Unexpected '}'.",
+                "[line 8, column 8] Syntax error at end: Expect ';' after value.",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_assignment_targets_are_reported_at_the_equals() {
+        assert_eq!(
+            errors("a + b = 1;\n(a) = 2;\nf() = 3; a = b = 4;"),
+            vec![
+                "[line 1, column 7] Syntax error at '=': Invalid assignment target.",
+                "[line 2, column 5] Syntax error at '=': Invalid assignment target.",
+                "[line 3, column 5] Syntax error at '=': Invalid assignment target.",
+            ]
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use crate::scanner::token::Token;
+use crate::scanner::token_type::TokenType;
+
+/// A syntax error, reported at the token where parsing went wrong.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    token: Box<Token>, // boxed to keep the `Err` side of parser results small
+    message: String,
+}
+
+impl ParseError {
+    pub fn new(token: Token, message: &str) -> ParseError {
+        ParseError { token: Box::new(token), message: message.to_string() }
+    }
+
+    pub fn get_token(&self) -> &Token { &self.token }
+    pub fn get_message(&self) -> &str { &self.message }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[line {}, column {}] Syntax error", self.token.get_line(), self.token.get_column())?;
+        if self.token.get_type() == TokenType::Eof {
+            write!(f, " at end: {}", self.message)
+        } else {
+            write!(f, " at '{}': {}", self.token.get_lexeme(), self.message)
+        }
+    }
+}
\ No newline at end of file
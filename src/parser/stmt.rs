@@ -31,7 +31,9 @@
 // breakStmt      → "break" ";" ;
 // continueStmt   → "continue" ";" ;
 
+use std::rc::Rc;
 use crate::parser::expr::Expr;
+use crate::resolver::Slot;
 use crate::scanner::token::Token;
 
 #[derive(Debug, Clone)]
@@ -46,12 +48,7 @@ pub enum Stmt {
     Var {
         name: Token,
         initializer: Option<Expr>,
-    },
-
-    /// IDENTIFIER "=" expression ;
-    Assignment {
-        name: Token,
-        value: Expr,
+        slot: Option<Slot>,
     },
 
     /// { declaration* }
@@ -65,13 +62,16 @@ pub enum Stmt {
     },
 
     /// while ( condition ) body
+    /// `increment` only comes from a lowered `for`, and runs after every
+    /// iteration, including ones cut short by `continue`.
     While {
         condition: Expr,
         body: Box<Stmt>,
+        increment: Option<Expr>,
     },
 
     /// for ( init ; cond ; inc ) body
-    /// (Will be desugared into Block + While)
+    /// Desugared into Block + While by `lowering::lower`.
     For {
         initializer: Option<Box<Stmt>>,
         condition: Option<Expr>,
@@ -80,10 +80,12 @@ pub enum Stmt {
     },
 
     /// fun IDENTIFIER (params) { body }
+    /// Parameters and body are shared with the function values created from it.
     Function {
         name: Token,
-        params: Vec<Token>,
-        body: Box<Stmt>,
+        params: Rc<[Token]>,
+        body: Rc<[Stmt]>,
+        slot: Option<Slot>,
     },
 
     /// return expression? ;
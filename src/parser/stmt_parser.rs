@@ -1,67 +1,104 @@
 use crate::parser::Parser;
+use crate::parser::parse_error::ParseError;
 use crate::parser::stmt::Stmt;
+use crate::scanner::token::Token;
 use crate::scanner::token_type::TokenType;
 
-impl Parser {
+impl Parser<'_> {
     // program → declaration* EOF ;
-    pub fn parse_program(&mut self) -> Vec<Stmt> {
+    /// Parses the whole program, recovering after each syntax error so that
+    /// every error in the source is reported, not just the first one.
+    pub fn parse_program(&mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
         let mut statements = Vec::new();
         while !self.is_eof() {
-            statements.push(self.declaration());
+            if self.check(TokenType::RightBrace) {
+                // no block is open, so nothing else will consume it
+                let error = self.error(self.peek(), "Unexpected '}'.");
+                self.errors.push(error);
+                self.advance();
+                continue;
+            }
+            if let Some(stmt) = self.declaration() {
+                statements.push(stmt);
+            }
+        }
+        if self.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(std::mem::take(&mut self.errors))
         }
-        statements
     }
 
     // declaration → funDecl | varDecl | statement ;
-    fn declaration(&mut self) -> Stmt {
-        if self.match_token(&[TokenType::Fun]) {
+    /// Records a failed declaration's error and skips to the next statement.
+    fn declaration(&mut self) -> Option<Stmt> {
+        // `fun` followed by a name declares; `fun (` starts a lambda expression
+        let stmt = if self.check(TokenType::Fun) && self.peek_next().get_type() == TokenType::Identifier {
+            self.advance();
             self.fun_declaration()
         } else if self.match_token(&[TokenType::Var]) {
             self.var_declaration()
         } else {
             self.statement()
+        };
+
+        match stmt {
+            Ok(stmt) => Some(stmt),
+            Err(error) => {
+                self.errors.push(error);
+                self.synchronize();
+                None
+            }
         }
     }
 
     // funDecl → "fun" IDENTIFIER "(" parameters? ")" block ;
-    fn fun_declaration(&mut self) -> Stmt {
-        let name = self.consume(TokenType::Identifier, "Expect function name.").clone();
+    fn fun_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(TokenType::Identifier, "Expect function name.")?.clone();
 
-        self.consume(TokenType::LeftParen, "Expect '(' after function name.");
+        self.consume(TokenType::LeftParen, "Expect '(' after function name.")?;
+        let (params, body) = self.function_rest()?;
+        Ok(Stmt::Function { name, params: params.into(), body: body.into(), slot: None })
+    }
+
+    // parameters → IDENTIFIER ( "," IDENTIFIER )* ;
+    /// Parameters and body of a declaration or lambda, after the opening '('.
+    pub(super) fn function_rest(&mut self) -> Result<(Vec<Token>, Vec<Stmt>), ParseError> {
         let mut params = Vec::new();
 
         if !self.check(TokenType::RightParen) {
             loop {
-                params.push(self.consume(TokenType::Identifier, "Expect parameter name.").clone());
+                params.push(self.consume(TokenType::Identifier, "Expect parameter name.")?.clone());
                 if !self.match_token(&[TokenType::Comma]) {
                     break;
                 }
             }
         }
 
-        self.consume(TokenType::RightParen, "Expect ')' after parameters.");
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before function body.")?;
 
-        let body =  self.parse_block();
-        Stmt::Function { name, params, body: Box::new(body)}
+        let body = self.block_statements()?;
+        Ok((params, body))
     }
 
     // varDecl → "var" IDENTIFIER ( "=" expression )? ";" ;
-    fn var_declaration(&mut self) -> Stmt {
-        let name = self.consume(TokenType::Identifier, "Expect variable name.").clone();
+    fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.consume(TokenType::Identifier, "Expect variable name.")?.clone();
 
         let initializer = if self.match_token(&[TokenType::Equal]) {
-            Some(self.parse_expression())
+            Some(self.parse_expression()?)
         } else {
             None
         };
 
-        self.consume(TokenType::Semicolon, "Expect ';' after variable declaration.");
+        self.consume(TokenType::Semicolon, "Expect ';' after variable declaration.")?;
 
-        Stmt::Var { name, initializer }
+        Ok(Stmt::Var { name, initializer, slot: None })
     }
 
-    // statement → exprStmt | printStmt | block | ifStmt | whileStmt | forStmt | returnStmt | breakStmt | continueStmt ;
-    fn statement(&mut self) -> Stmt {
+    // statement → exprStmt | printStmt | block | ifStmt | whileStmt | forStmt | returnStmt | breakStmt | continueStmt | varDecl ;
+    fn statement(&mut self) -> Result<Stmt, ParseError> {
         if self.match_token(&[TokenType::Print]) {
             self.print_statement()
         } else if self.match_token(&[TokenType::LeftBrace]) {
@@ -78,149 +115,137 @@ impl Parser {
             self.parse_break_statement()
         } else if self.match_token(&[TokenType::Continue]) {
             self.parse_continue_statement()
-        }
-        else if self.match_token(&[TokenType::Var]) {
-                self.parse_var_declaration()
-        }
-        else {
+        } else if self.match_token(&[TokenType::Var]) {
+            self.var_declaration()
+        } else {
             self.expression_statement()
         }
     }
 
-    fn parse_var_declaration(&mut self) -> Stmt {
-        let iden = self.consume(TokenType::Identifier, "Expect ';' after variable declaration.").clone();
-        let contains_equal = self.check(TokenType::Equal).clone();
-
-        let stmt  = match contains_equal {
-            true => {
-                self.consume(TokenType::Equal, "Expect '=' after variable declaration.");
-                let expr = self.parse_expression();
-                Stmt::Var {name: iden.clone(), initializer: Some(expr)}
-            }
-            _ => {
-                Stmt::Var {name: iden.clone(), initializer: None}
-            }
-        };
-        stmt
-    }
-
     // exprStmt → expression ";" ;
-    fn expression_statement(&mut self) -> Stmt {
-        let expr = self.parse_expression();
-        self.consume(TokenType::Semicolon, "Expect ';' after expression.");
-        Stmt::Expression(expr)
+    fn expression_statement(&mut self) -> Result<Stmt, ParseError> {
+        let expr = self.parse_expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+        Ok(Stmt::Expression(expr))
     }
 
     // printStmt → "print" expression ";" ;
-    fn print_statement(&mut self) -> Stmt {
-        let expr = self.parse_expression();
-        self.consume(TokenType::Semicolon, "Expect ';' after value.");
-        Stmt::Print(expr)
+    fn print_statement(&mut self) -> Result<Stmt, ParseError> {
+        let expr = self.parse_expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+        Ok(Stmt::Print(expr))
     }
 
     // block → "{" declaration* "}" ;
-    fn parse_block(&mut self) -> Stmt {
+    fn parse_block(&mut self) -> Result<Stmt, ParseError> {
+        Ok(Stmt::Block{stmts: self.block_statements()?})
+    }
+
+    /// The statements of a block whose '{' has been consumed, up to and including the '}'.
+    fn block_statements(&mut self) -> Result<Vec<Stmt>, ParseError> {
         let mut statements = Vec::new();
         while !self.check(TokenType::RightBrace) && !self.is_eof() {
-            statements.push(self.declaration());
+            if let Some(stmt) = self.declaration() {
+                statements.push(stmt);
+            }
         }
 
-        self.consume(TokenType::RightBrace, "Expect '}' after block.");
-        Stmt::Block{stmts: statements}
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
     }
 
     // ifStmt → "if" "(" expression ")" statement ( "else" statement )? ;
-    fn parse_if_statement(&mut self) -> Stmt {
-        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.");
-        let condition = self.parse_expression();
-        self.consume(TokenType::RightParen, "Expect ')' after if condition.");
+    fn parse_if_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.parse_expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
 
-        let then_branch = Box::new(self.statement());
+        let then_branch = Box::new(self.statement()?);
         let else_branch = if self.match_token(&[TokenType::Else]) {
-            Some(Box::new(self.statement()))
+            Some(Box::new(self.statement()?))
         } else {
             None
         };
 
-        Stmt::If {
+        Ok(Stmt::If {
             condition,
             then_branch,
             else_branch,
-        }
+        })
     }
 
     // whileStmt → "while" "(" expression ")" statement ;
-    fn parse_while_statement(&mut self) -> Stmt {
-        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.");
-        let condition = self.parse_expression();
-        self.consume(TokenType::RightParen, "Expect ')' after while condition.");
+    fn parse_while_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.parse_expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after while condition.")?;
 
-        let body = Box::new(self.statement());
-        Stmt::While { condition, body }
+        let body = Box::new(self.statement()?);
+        Ok(Stmt::While { condition, body, increment: None })
     }
 
     // forStmt → "for" "(" ( varDecl | exprStmt | ";" ) expression? ";" expression? ")" statement ;
-    fn parse_for_statement(&mut self) -> Stmt {
-        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.");
+    fn parse_for_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
 
         // Parse initializer
         let initializer = if self.match_token(&[TokenType::Semicolon]) {
             None
         } else if self.match_token(&[TokenType::Var]) {
-            Some(Box::new(self.var_declaration()))
+            Some(Box::new(self.var_declaration()?))
         } else {
-            Some(Box::new(self.expression_statement()))
+            Some(Box::new(self.expression_statement()?))
         };
 
         // Parse condition
         let condition = if !self.check(TokenType::Semicolon) {
-            Some(self.parse_expression())
+            Some(self.parse_expression()?)
         } else {
             None
         };
-        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.");
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
 
         // Parse increment
         let increment = if !self.check(TokenType::RightParen) {
-            Some(self.parse_expression())
+            Some(self.parse_expression()?)
         } else {
             None
         };
-        self.consume(TokenType::RightParen, "Expect ')' after for clauses.");
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
 
-        let body = Box::new(self.statement());
+        let body = Box::new(self.statement()?);
 
-        Stmt::For {
+        Ok(Stmt::For {
             initializer,
             condition,
             increment,
             body,
-        }
+        })
     }
 
     // returnStmt → "return" expression? ";" ;
-    fn parse_return_statement(&mut self) -> Stmt {
+    fn parse_return_statement(&mut self) -> Result<Stmt, ParseError> {
         let keyword = self.previous().clone();
         let value = if !self.check(TokenType::Semicolon) {
-            Some(self.parse_expression())
+            Some(self.parse_expression()?)
         } else {
             None
         };
-        self.consume(TokenType::Semicolon, "Expect ';' after return value.");
-        Stmt::Return { keyword, value }
+        self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+        Ok(Stmt::Return { keyword, value })
     }
 
     // breakStmt → "break" ";" ;
-    fn parse_break_statement(&mut self) -> Stmt {
+    fn parse_break_statement(&mut self) -> Result<Stmt, ParseError> {
         let keyword = self.previous().clone();
-        self.consume(TokenType::Semicolon, "Expect ';' after 'break'.");
-        Stmt::Break { keyword }
+        self.consume(TokenType::Semicolon, "Expect ';' after 'break'.")?;
+        Ok(Stmt::Break { keyword })
     }
 
     // continueStmt → "continue" ";" ;
-    fn parse_continue_statement(&mut self) -> Stmt {
+    fn parse_continue_statement(&mut self) -> Result<Stmt, ParseError> {
         let keyword = self.previous().clone();
-        self.consume(TokenType::Semicolon, "Expect ';' after 'continue'.");
-        Stmt::Continue { keyword }
+        self.consume(TokenType::Semicolon, "Expect ';' after 'continue'.")?;
+        Ok(Stmt::Continue { keyword })
     }
 }
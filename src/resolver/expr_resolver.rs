@@ -0,0 +1,42 @@
+use crate::parser::expr::Expr;
+use crate::resolver::Resolver;
+
+impl Resolver {
+    pub fn resolve_expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::Variable { name, slot } => {
+                // globals are looked up by name at runtime, so only locals can
+                // be read before they exist
+                if self.scopes.len() > 1
+                    && let Some(declared) = self.scopes.last().unwrap().get(name.get_lexeme())
+                    && !declared.defined {
+                    self.error(name, "Can't read local variable in its own initializer.");
+                }
+                *slot = self.resolve_local(name);
+            }
+            Expr::Assign { identifier, value, slot } => {
+                self.resolve_expr(value);
+                *slot = self.resolve_local(identifier);
+            }
+            Expr::Unary { right, .. } => self.resolve_expr(right),
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                self.resolve_expr(left);
+                self.resolve_expr(right);
+            }
+            Expr::Call { callee, arguments, .. } => {
+                self.resolve_expr(callee);
+                for argument in arguments {
+                    self.resolve_expr(argument);
+                }
+            }
+            Expr::Lambda { params, body, .. } => self.resolve_function(params, body),
+            Expr::Grouping(inner) => self.resolve_expr(inner),
+            Expr::Interpolation { parts } => {
+                for part in parts {
+                    self.resolve_expr(part);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
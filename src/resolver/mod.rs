@@ -0,0 +1,233 @@
+mod stmt_resolver;
+mod expr_resolver;
+pub mod resolve_error;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::lowering;
+use crate::parser::stmt::Stmt;
+use crate::resolver::resolve_error::ResolveError;
+use crate::scanner::token::Token;
+
+/// Where a local variable lives at runtime: `depth` scopes out from where it
+/// is used (0 is the innermost), at position `index` in that scope.
+/// The resolver stores it on the variable's AST node; globals have none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    depth: usize,
+    index: usize,
+}
+
+impl Slot {
+    pub fn get_depth(&self) -> usize { self.depth }
+    pub fn get_index(&self) -> usize { self.index }
+}
+
+/// A program the `Resolver` has accepted, with the slot of every local stored
+/// on its node. Only `Resolver::resolve` makes one, which is what lets the
+/// `Interpreter` trust the slots.
+#[derive(Debug, Clone)]
+pub struct ResolvedProgram {
+    stmts: Vec<Stmt>,
+}
+
+impl ResolvedProgram {
+    pub fn get_stmts(&self) -> &[Stmt] { &self.stmts }
+}
+
+/// A name declared in a scope: its slot and whether its initializer has finished.
+struct Declared {
+    index: usize,
+    defined: bool,
+}
+
+/// Walks the program once before it runs, working out which declaration each
+/// variable use refers to and reporting scoping mistakes as static errors.
+/// Its scopes mirror the environments the interpreter creates at runtime.
+pub struct Resolver {
+    scopes: Vec<HashMap<String, Declared>>,
+    in_function: bool,
+    loop_depth: usize,
+    errors: Vec<ResolveError>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Resolver {
+    pub fn new() -> Resolver {
+        Resolver {
+            scopes: vec![HashMap::new()], // global scope, only checked for duplicates
+            in_function: false,
+            loop_depth: 0,
+            errors: vec![],
+        }
+    }
+
+    /// Lowers and resolves a whole program, storing the slot of every local on
+    /// its node and collecting every error rather than stopping at the first.
+    pub fn resolve(mut self, stmts: Vec<Stmt>) -> Result<ResolvedProgram, Vec<ResolveError>> {
+        let mut stmts = lowering::lower(stmts);
+        self.resolve_stmts(&mut stmts);
+        if self.errors.is_empty() {
+            Ok(ResolvedProgram { stmts })
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn error(&mut self, token: &Token, message: &str) {
+        self.errors.push(ResolveError::new(token.clone(), message));
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Adds the name to the innermost scope in the next free slot, not yet usable.
+    /// Returns the slot, or None for a global.
+    fn declare(&mut self, name: &Token) -> Option<Slot> {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.contains_key(name.get_lexeme()) {
+            self.error(name, "Already a variable with this name in this scope.");
+            return None;
+        }
+        let index = scope.len();
+        scope.insert(name.get_lexeme().clone(), Declared { index, defined: false });
+        if self.scopes.len() > 1 { Some(Slot { depth: 0, index }) } else { None }
+    }
+
+    /// Marks the name as initialized, so it may be read from now on.
+    fn define(&mut self, name: &Token) {
+        if let Some(declared) = self.scopes.last_mut().unwrap().get_mut(name.get_lexeme()) {
+            declared.defined = true;
+        }
+    }
+
+    /// How many scopes out the name was declared and at which slot,
+    /// or None if it is global.
+    fn resolve_local(&self, name: &Token) -> Option<Slot> {
+        let innermost = self.scopes.len() - 1;
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if depth == innermost {
+                break;
+            }
+            if let Some(declared) = scope.get(name.get_lexeme()) {
+                return Some(Slot { depth, index: declared.index });
+            }
+        }
+        None
+    }
+
+    /// Functions get one scope holding both parameters and body. `break` and
+    /// `continue` cannot reach loops outside the function.
+    fn resolve_function(&mut self, params: &[Token], body: &mut Rc<[Stmt]>) {
+        let in_function = std::mem::replace(&mut self.in_function, true);
+        let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+
+        self.begin_scope();
+        for param in params {
+            self.declare(param); // slots 0..n, filled by the call's arguments
+            self.define(param);
+        }
+        // a body shared with a clone of the program is copied before slots are written
+        self.resolve_stmts(Rc::make_mut(body));
+        self.end_scope();
+
+        self.in_function = in_function;
+        self.loop_depth = loop_depth;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::expr::Expr;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+
+    fn parse(source: &str) -> Vec<Stmt> {
+        let mut scanner = Scanner::new(source.to_string());
+        Parser::from_tokens(&mut scanner).parse_program().unwrap()
+    }
+
+    fn resolve(source: &str) -> Result<ResolvedProgram, Vec<ResolveError>> {
+        Resolver::new().resolve(parse(source))
+    }
+
+    fn messages(source: &str) -> Vec<String> {
+        match resolve(source) {
+            Ok(_) => vec![],
+            Err(errors) => errors.iter().map(|error| error.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn duplicate_local() {
+        assert_eq!(
+            messages("{ var a = 1;\n  var a = 2; }"),
+            vec!["[line 2, column 7] Resolve error at 'a': Already a variable with this name in this scope."]
+        );
+    }
+
+    #[test]
+    fn local_read_in_own_initializer() {
+        assert_eq!(
+            messages("var a = 1; { var a = a; }"),
+            vec!["[line 1, column 22] Resolve error at 'a': Can't read local variable in its own initializer."]
+        );
+        assert_eq!(messages("var a = 1; var b = a;"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn top_level_return() {
+        assert_eq!(
+            messages("return 1;"),
+            vec!["[line 1, column 1] Resolve error at 'return': Can't return from top-level code."]
+        );
+    }
+
+    #[test]
+    fn break_and_continue_outside_a_loop() {
+        assert_eq!(
+            messages("break;\nwhile (true) { fun f() { continue; } }"),
+            vec![
+                "[line 1, column 1] Resolve error at 'break': Can't use 'break' outside of a loop.",
+                "[line 2, column 26] Resolve error at 'continue': Can't use 'continue' outside of a loop.",
+            ]
+        );
+    }
+
+    #[test]
+    fn locals_get_the_slot_they_were_declared_in() {
+        let program = resolve("{ var a = 1; var b = 2; { print b; } }").unwrap();
+        let Stmt::Block { stmts } = &program.get_stmts()[0] else { panic!("expected a block") };
+        let Stmt::Block { stmts } = &stmts[2] else { panic!("expected a block") };
+        let Stmt::Print(Expr::Variable { slot, .. }) = &stmts[0] else { panic!("expected print b") };
+        assert_eq!(*slot, Some(Slot { depth: 1, index: 1 }));
+    }
+
+    #[test]
+    fn a_cloned_program_resolves_on_its_own_copy() {
+        let stmts = parse("fun f(a) { { print a; } }");
+        let copy = Resolver::new().resolve(stmts.clone()).unwrap();
+
+        let Stmt::Function { body: original, .. } = &stmts[0] else { panic!("expected a function") };
+        let Stmt::Function { body: copied, .. } = &copy.get_stmts()[0] else { panic!("expected a function") };
+        assert!(!Rc::ptr_eq(original, copied));
+    }
+
+    #[test]
+    fn for_loops_straight_from_the_parser_are_lowered() {
+        let program = resolve("for (var i = 0; i < 2; i = i + 1) print i;").unwrap();
+        let Stmt::Block { stmts } = &program.get_stmts()[0] else { panic!("expected a block") };
+        assert!(matches!(stmts[1], Stmt::While { increment: Some(_), .. }));
+    }
+}
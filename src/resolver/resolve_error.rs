@@ -0,0 +1,29 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use crate::scanner::token::Token;
+
+/// A scoping error found before the program runs, reported at the offending token.
+#[derive(Debug, Clone)]
+pub struct ResolveError {
+    token: Box<Token>,
+    message: String,
+}
+
+impl ResolveError {
+    pub fn new(token: Token, message: &str) -> ResolveError {
+        ResolveError { token: Box::new(token), message: message.to_string() }
+    }
+
+    pub fn get_token(&self) -> &Token { &self.token }
+    pub fn get_message(&self) -> &str { &self.message }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[line {}, column {}] Resolve error at '{}': {}",
+            self.token.get_line(), self.token.get_column(), self.token.get_lexeme(), self.message
+        )
+    }
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+use crate::parser::stmt::Stmt;
+use crate::resolver::Resolver;
+
+impl Resolver {
+    pub fn resolve_stmts(&mut self, stmts: &mut [Stmt]) {
+        for stmt in stmts {
+            self.resolve_stmt(stmt);
+        }
+    }
+
+    pub fn resolve_stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Expression(expr) | Stmt::Print(expr) => self.resolve_expr(expr),
+            Stmt::Var { name, initializer, slot } => {
+                *slot = self.declare(name);
+                if let Some(initializer) = initializer {
+                    self.resolve_expr(initializer);
+                }
+                self.define(name);
+            }
+            Stmt::Block { stmts } => {
+                self.begin_scope();
+                self.resolve_stmts(stmts);
+                self.end_scope();
+            }
+            Stmt::If { condition, then_branch, else_branch } => {
+                self.resolve_expr(condition);
+                self.resolve_stmt(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.resolve_stmt(else_branch);
+                }
+            }
+            Stmt::While { condition, body, increment } => {
+                self.resolve_expr(condition);
+                self.resolve_loop_body(body);
+                if let Some(increment) = increment {
+                    self.resolve_expr(increment);
+                }
+            }
+            Stmt::For { .. } => unreachable!("`for` is lowered to `while` before resolving"),
+            Stmt::Function { name, params, body, slot } => {
+                // defined before the body so the function can call itself
+                *slot = self.declare(name);
+                self.define(name);
+                self.resolve_function(params, body);
+            }
+            Stmt::Return { keyword, value } => {
+                if !self.in_function {
+                    self.error(keyword, "Can't return from top-level code.");
+                }
+                if let Some(value) = value {
+                    self.resolve_expr(value);
+                }
+            }
+            Stmt::Break { keyword } => {
+                if self.loop_depth == 0 {
+                    self.error(keyword, "Can't use 'break' outside of a loop.");
+                }
+            }
+            Stmt::Continue { keyword } => {
+                if self.loop_depth == 0 {
+                    self.error(keyword, "Can't use 'continue' outside of a loop.");
+                }
+            }
+        }
+    }
+
+    fn resolve_loop_body(&mut self, body: &mut Stmt) {
+        self.loop_depth += 1;
+        self.resolve_stmt(body);
+        self.loop_depth -= 1;
+    }
+}
\ No newline at end of file
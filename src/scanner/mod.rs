@@ -1,41 +1,174 @@
-use std::iter::Scan;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+use unicode_xid::UnicodeXID;
+use crate::scanner::scan_error::{ScanError, ScanErrorKind};
+use crate::scanner::source_map::{FileId, SourceMap, Span};
 use crate::scanner::token::Token;
 use crate::scanner::token_type::TokenType;
+use crate::scanner::trivia::{Trivia, TriviaKind};
 
 pub mod token_type;
 pub mod token;
+pub mod scan_error;
+pub mod source_map;
+pub mod trivia;
 
 pub struct Scanner {
-    source: String,
+    source: Rc<str>,
+    file: FileId,
     tokens: Vec<Token>,
+    queue: VecDeque<Token>, // scanned but not yet handed out by `next`
+    finished: bool,         // the Eof token has been queued
+    errors: Vec<ScanError>,
+    interpolations: Vec<OpenInterpolation>,
+    keep_trivia: bool,
+    pending_trivia: Vec<Trivia>, // leading trivia of the next token
     start: usize,
+    start_line: usize,
+    start_column: usize,
     current : usize,
     line: usize,
+    column: usize,
+}
+
+/// A `${` inside a string literal whose closing `}` has not been seen yet.
+struct OpenInterpolation {
+    braces: usize, // unmatched '{' inside the embedded expression
+    start: usize,
+    line: usize,
+    column: usize,
 }
 
 impl Scanner {
+    /// Scans a standalone source. Its spans use the default `FileId`, which is
+    /// also the id of the first file added to a `SourceMap`.
     pub fn new(source: String) -> Scanner {
-        Scanner{source, tokens: vec![], start: 0, current: 0, line: 1}
+        Scanner::with_file(FileId::default(), Rc::from(source))
+    }
+
+    /// Scans one file of a `SourceMap`; token spans point back into it.
+    pub fn for_file(source_map: &SourceMap, file: FileId) -> Scanner {
+        Scanner::with_file(file, source_map.get_file(file).get_source().clone())
+    }
+
+    /// Keeps whitespace, newlines and comments as trivia on the neighbouring
+    /// tokens instead of dropping them, so tools can rebuild the exact source.
+    /// Trivia up to the end of a line trails the token before it; everything
+    /// else leads the token after it.
+    pub fn with_trivia(mut self) -> Scanner {
+        self.keep_trivia = true;
+        self
+    }
+
+    fn with_file(file: FileId, source: Rc<str>) -> Scanner {
+        Scanner{
+            source, file, tokens: vec![], queue: VecDeque::new(), finished: false,
+            errors: vec![], interpolations: vec![],
+            keep_trivia: false, pending_trivia: vec![],
+            start: 0, start_line: 1, start_column: 1, current: 0, line: 1, column: 1,
+        }
     }
     
     pub fn get_tokens(& self) -> &Vec<Token> { &self.tokens }
+    pub fn get_errors(& self) -> &Vec<ScanError> { &self.errors }
+
+    /// Scans the whole source. Lexing carries on past errors, so on failure
+    /// every problem in the file is returned; the tokens that could be read
+    /// are still available through `get_tokens`.
+    ///
+    /// To lex lazily instead, use the scanner as an `Iterator<Item = Token>`;
+    /// errors found so far are then available through `get_errors`.
+    pub fn scan_tokens(&mut self) -> Result<&Vec<Token>, Vec<ScanError>> {
+        self.tokens = self.by_ref().collect();
+
+        if self.errors.is_empty() {
+            Ok(&self.tokens)
+        } else {
+            Err(self.errors.clone())
+        }
+    }
 
-    pub fn scan_tokens(&mut self) -> &Vec<Token>{
-        while !self.is_eof() {
-            self.start = self.current;
-            self.scan_token();
+    /// Scans one lexeme; it may queue a token or, for whitespace, comments
+    /// and errors, nothing at all.
+    fn scan_lexeme(&mut self) {
+        self.start = self.current;
+        self.start_line = self.line;
+        self.start_column = self.column;
+        let queued = self.queue.len();
+        self.scan_token();
+        if self.keep_trivia && self.queue.len() == queued {
+            self.add_trivia();
         }
-        self.tokens.push(Token::new(TokenType::Eof, "".to_string(), self.start, self.line, self.line ));
+    }
 
-        &self.tokens
+    fn finish(&mut self) {
+        while let Some(open) = self.interpolations.pop() {
+            self.error_at(ScanErrorKind::UnterminatedInterpolation, open.start, open.line, open.column);
+        }
+        self.start = self.current;
+        self.start_line = self.line;
+        self.start_column = self.column;
+        self.add_token_with_lexeme(TokenType::Eof, "".to_string());
+        self.finished = true;
+    }
+
+    /// Whether the front of the queue can be handed out. When keeping trivia
+    /// a token waits until its trailing trivia (the rest of its line) is known.
+    fn front_ready(&self) -> bool {
+        match self.queue.len() {
+            0 => false,
+            1 => !self.keep_trivia || self.finished || !self.pending_trivia.is_empty(),
+            _ => true,
+        }
+    }
+
+    /// Records an error covering the current lexeme.
+    fn error(&mut self, kind: ScanErrorKind) {
+        self.error_at(kind, self.start, self.start_line, self.start_column);
+    }
+
+    /// Records an error covering `start..self.current` for a problem inside the
+    /// lexeme, e.g. an escape; `line` and `column` are those of `start`.
+    fn error_at(&mut self, kind: ScanErrorKind, start: usize, line: usize, column: usize) {
+        let span = Span::new(self.file, start, self.current);
+        self.errors.push(ScanError::new(kind, span, line, column));
     }
 
     fn add_token(&mut self, token: TokenType){
-        let text = &self.source[self.start..self.current];
-        self.tokens.push(Token::new(token, text.to_string(), self.start, self.current, self.line ));
+        let text = self.source[self.start..self.current].to_string();
+        self.add_token_with_lexeme(token, text);
     }
     fn add_token_with_lexeme(&mut self, token: TokenType, text: String){
-        self.tokens.push(Token::new(token, text.to_string(), self.start, self.current, self.line ));
+        let span = Span::new(self.file, self.start, self.current);
+        let mut token = Token::new(token, text, span, self.start_line, self.start_column);
+        if self.keep_trivia {
+            token.set_leading_trivia(std::mem::take(&mut self.pending_trivia));
+        }
+        self.queue.push_back(token);
+    }
+
+    /// Records the text just scanned, which produced no token, as trivia.
+    fn add_trivia(&mut self) {
+        let text = &self.source[self.start..self.current];
+        let kind = if text.starts_with("//") {
+            TriviaKind::LineComment
+        } else if text.starts_with("/*") {
+            TriviaKind::BlockComment
+        } else if text == "\n" {
+            TriviaKind::Newline
+        } else if text.chars().all(|c| c == ' ' || c == '\r' || c == '\t') {
+            TriviaKind::Whitespace
+        } else {
+            TriviaKind::Skipped
+        };
+
+        let trailing = self.pending_trivia.is_empty() && kind != TriviaKind::Newline;
+        let trivia = Trivia::new(kind, text.to_string(), Span::new(self.file, self.start, self.current));
+        match self.queue.back_mut() {
+            Some(token) if trailing => token.trailing_trivia_mut().push(trivia),
+            _ => self.pending_trivia.push(trivia),
+        }
     }
 
     fn scan_token(&mut self){
@@ -45,8 +178,24 @@ impl Scanner {
 
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some(open) = self.interpolations.last_mut() {
+                    open.braces += 1;
+                }
+                self.add_token(TokenType::LeftBrace)
+            }
+            '}' => match self.interpolations.last_mut() {
+                // closes a `${ ... }`, the string literal carries on
+                Some(open) if open.braces == 0 => {
+                    self.interpolations.pop();
+                    self.string();
+                }
+                Some(open) => {
+                    open.braces -= 1;
+                    self.add_token(TokenType::RightBrace)
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
             ',' => self.add_token(TokenType::Comma),
             '.' => self.add_token(TokenType::Dot),
             '-' => self.add_token(TokenType::Minus),
@@ -54,13 +203,17 @@ impl Scanner {
             ';' => self.add_token(TokenType::Semicolon),
             '*' => self.add_token(TokenType::Star),
 
-        // single line comment and division
+        // single line comment, block comment and division
             '/' => {
-                if(self.peek() == '/'){
+                if self.peek() == '/' {
                     while self.peek() != '\n' && !self.is_eof(){
                         self.advance();
                     }
                 }
+                else if self.peek() == '*' {
+                    self.advance(); // consume '*'
+                    self.block_comment();
+                }
                 else{
                     self.add_token(TokenType::Slash);
                 }
@@ -102,59 +255,232 @@ impl Scanner {
 
 
         //  WhiteSpaces
-            ' ' | '\r' | '\t' => {},
-            '\n' => self.line += 1,
+            ' ' | '\r' | '\t' => {
+                while matches!(self.peek(), ' ' | '\r' | '\t') {
+                    self.advance();
+                }
+            }
+            '\n' => {},
 
-        // strings
-            c if c == '"' => self.string(),
+        // strings: "...", raw r"..." and multi-line raw """..."""
+            '"' => {
+                if self.peek() == '"' && self.peek_next() == '"' {
+                    self.advance();
+                    self.advance();
+                    self.raw_string("\"\"\"");
+                } else {
+                    self.string();
+                }
+            }
+            'r' if self.peek() == '"' => {
+                self.advance();
+                self.raw_string("\"");
+            }
 
         //  Numbers
             c if c.is_ascii_digit() => self.number(),
 
-        //  Alphanumeric, eg: identifier & keywords
-            c if c.is_alphanumeric() || c == '_' => self.identifier(),
+        //  Identifiers & keywords: XID_Start or '_', then XID_Continue
+            c if c.is_xid_start() || c == '_' => self.identifier(),
 
         //  unexpected token
-            _ => { println!("Unexpected character '{}' at line {}, character {}", c, self.line, self.current ); }
+            _ => self.error(ScanErrorKind::UnexpectedCharacter(c)),
+        }
+    }
+
+    /// Skips a `/* ... */` comment whose opening `/*` is already consumed.
+    /// Comments nest, so `/* a /* b */ c */` is a single comment.
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_eof() {
+                self.error(ScanErrorKind::UnterminatedComment);
+                return;
+            }
+            match self.advance() {
+                '/' if self.peek() == '*' => {
+                    self.advance();
+                    depth += 1;
+                }
+                '*' if self.peek() == '/' => {
+                    self.advance();
+                    depth -= 1;
+                }
+                _ => {}
+            }
         }
     }
 
+    /// Scans string text up to the closing `"`, or up to a `${` that starts an
+    /// embedded expression. A string with embedded expressions lexes as
+    /// `Interpolation` tokens, each followed by the expression's tokens, and
+    /// ends with an ordinary `String` token for the text after the last `}`.
     fn string(&mut self){
-        if(self.peek() != '"' && !self.is_eof()){
-            if(self.peek() == '\n'){self.line += 1;}
+        let mut value = String::new();
+        while self.peek() != '"' && !self.is_eof() {
+            if self.peek() == '$' && self.peek_next() == '{' {
+                self.advance();
+                self.advance();
+                self.interpolations.push(OpenInterpolation {
+                    braces: 0, start: self.current - 2, line: self.line, column: self.column - 2,
+                });
+                self.add_token_with_lexeme(TokenType::Interpolation, value);
+                return;
+            }
+            match self.advance() {
+                '\\' => self.escape(&mut value),
+                c => value.push(c),
+            }
+        }
+        if self.is_eof() {
+            self.error(ScanErrorKind::UnterminatedString);
+            return;
+        }
+        self.advance(); // closing '"'
+        self.add_token_with_lexeme(TokenType::String, value);
+    }
+
+    /// Scans a raw string whose opening delimiter is consumed, up to `close`.
+    /// Backslashes, `${` and newlines are kept exactly as written.
+    fn raw_string(&mut self, close: &str) {
+        let content_start = self.current;
+        while !self.rest().starts_with(close) {
+            if self.is_eof() {
+                self.error(ScanErrorKind::UnterminatedString);
+                return;
+            }
+            self.advance();
+        }
+        let value = self.source[content_start..self.current].to_string();
+        for _ in close.chars() {
             self.advance();
         }
-        if(self.is_eof()){
-            println!("Unexpected end of string at line {}, character {}", self.line, self.current );
+        self.add_token_with_lexeme(TokenType::String, value);
+    }
+
+    /// Decodes the escape sequence after a `\` and appends it to `value`.
+    /// Invalid escapes are reported and dropped so the rest of the string still lexes.
+    fn escape(&mut self, value: &mut String) {
+        let escape_start = self.current - 1;
+        let escape_line = self.line; // the escaped char may be a newline
+        let escape_column = self.column - 1;
+
+        if self.is_eof() {
+            return; // reported as an unterminated string
+        }
+        match self.advance() {
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            'r' => value.push('\r'),
+            '"' => value.push('"'),
+            '\\' => value.push('\\'),
+            '0' => value.push('\0'),
+            '$' => value.push('$'),
+            'u' => self.unicode_escape(escape_start, escape_line, escape_column, value),
+            c => self.error_at(ScanErrorKind::InvalidEscape(c), escape_start, escape_line, escape_column),
         }
-        let str = &self.source[self.start+1 .. self.current-1];
-        self.add_token_with_lexeme(TokenType::String, str.to_string());
     }
+
+    /// `\u{XXXX}`: one to six hex digits naming a Unicode scalar value.
+    fn unicode_escape(&mut self, escape_start: usize, escape_line: usize, escape_column: usize, value: &mut String) {
+        if self.peek() != '{' {
+            self.error_at(ScanErrorKind::InvalidUnicodeEscape, escape_start, escape_line, escape_column);
+            return;
+        }
+        self.advance(); // consume '{'
+
+        let digits_start = self.current;
+        while self.peek().is_ascii_hexdigit() {
+            self.advance();
+        }
+        let digits = &self.source[digits_start..self.current];
+        if digits.is_empty() || digits.len() > 6 || self.peek() != '}' {
+            self.error_at(ScanErrorKind::InvalidUnicodeEscape, escape_start, escape_line, escape_column);
+            return;
+        }
+        let code = u32::from_str_radix(digits, 16).ok();
+        self.advance(); // consume '}'
+
+        match code.and_then(char::from_u32) {
+            Some(c) => value.push(c),
+            None => self.error_at(ScanErrorKind::InvalidUnicodeEscape, escape_start, escape_line, escape_column),
+        }
+    }
+    // number  → "0x" HEX_DIGITS | "0b" BIN_DIGITS | "0o" OCT_DIGITS | decimal ;
+    // decimal → DIGITS ( "." DIGITS )? ( ( "e" | "E" ) ( "+" | "-" )? DIGITS )? ;
+    // Every digit run may use `_` between digits, e.g. 1_000_000 or 0xFF_FF.
     fn number(&mut self){
-        while(self.peek().is_ascii_digit()){
+        let radix = match (&self.source[self.start..self.current], self.peek()) {
+            ("0", 'x' | 'X') => Some(16),
+            ("0", 'b' | 'B') => Some(2),
+            ("0", 'o' | 'O') => Some(8),
+            _ => None,
+        };
+        let valid = match radix {
+            Some(radix) => {
+                self.advance(); // consume the prefix letter
+                let digits_start = self.current;
+                while self.peek().is_xid_continue() {
+                    self.advance();
+                }
+                is_digit_run(&self.source[digits_start..self.current], radix)
+            }
+            None => self.decimal(),
+        };
+
+        // a number running straight into letters, e.g. `12abc` or `0b102`
+        if !valid || self.peek().is_xid_continue() {
+            while self.peek().is_xid_continue() {
+                self.advance();
+            }
+            self.error(ScanErrorKind::MalformedNumber);
+            // still a number to the parser, so it reports no follow-on error
+            self.add_token_with_lexeme(TokenType::Number, "0".to_string());
+            return;
+        }
+        self.add_token(TokenType::Number);
+    }
+
+    /// Scans the rest of a decimal literal whose first digit is consumed,
+    /// returning whether every digit run in it is well formed.
+    fn decimal(&mut self) -> bool {
+        let mut valid = self.decimal_digits(self.start);
+
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.advance();
+            valid &= self.decimal_digits(self.current);
         }
-        if(self.peek() == '.' && self.peek_next().is_ascii_digit()){
+        if self.peek() == 'e' || self.peek() == 'E' {
             self.advance();
-            while(self.peek().is_ascii_digit()){
+            if self.peek() == '+' || self.peek() == '-' {
                 self.advance();
             }
+            valid &= self.decimal_digits(self.current);
         }
-        else{
-            println!("Unexpected digit '{}' at line {}, character {}", self.peek(), self.line, self.current );
+        valid
+    }
+
+    fn decimal_digits(&mut self, run_start: usize) -> bool {
+        while self.peek().is_ascii_digit() || self.peek() == '_' {
+            self.advance();
         }
-        self.add_token(TokenType::Number);
+        is_digit_run(&self.source[run_start..self.current], 10)
     }
+    /// Identifier lexemes are NFC-normalized, so names that look the same,
+    /// e.g. a precomposed `é` and `e` + combining accent, are the same name.
     fn identifier(&mut self) {
 
-        while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+        while self.peek().is_xid_continue() {
             self.advance();
         }
-        let text = &self.source[self.start..self.current];
+        let raw = &self.source[self.start..self.current];
+        let text = if is_nfc(raw) { raw.to_string() } else { raw.nfc().collect() };
 
-        let token_type = match text {
+        let token_type = match text.as_str() {
             "and" => TokenType::And,
+            "break" => TokenType::Break,
             "class" => TokenType::Class,
+            "continue" => TokenType::Continue,
             "else" => TokenType::Else,
             "false" => TokenType::False,
             "for" => TokenType::For,
@@ -171,24 +497,279 @@ impl Scanner {
             "while" => TokenType::While,
             _ => TokenType::Identifier,
         };
-        self.add_token(token_type);
+        self.add_token_with_lexeme(token_type, text);
     }
 
     // ***************************** helper functions **************************
+    // `start` and `current` are byte offsets into `source` and always sit on a
+    // char boundary, so looking ahead only decodes the next char or two
+    // instead of walking the string from the beginning.
+    fn rest(&self) -> &str {
+        &self.source[self.current..]
+    }
+
     fn peek(&self)-> char {
-        self.source.chars().nth(self.current).unwrap_or('\0')
+        self.rest().chars().next().unwrap_or('\0')
     }
 
     fn peek_next(&self)-> char {
-        self.source.chars().nth(self.current+1).unwrap_or('\0')
+        self.rest().chars().nth(1).unwrap_or('\0')
     }
     fn is_eof(&self) -> bool {
         self.current >= self.source.len()
     }
 
+    // Line and column are kept up to date here so every path through the
+    // scanner, including strings and comments, counts newlines the same way.
     fn advance(&mut self) -> char {
-        let ch = self.source.chars().nth(self.current).unwrap();
+        let ch = self.rest().chars().next().unwrap_or('\0');
         self.current += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
         ch
     }
-}
\ No newline at end of file
+}
+
+/// Hands out tokens as they are scanned, ending with a single `Eof` token.
+impl Iterator for Scanner {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        while !self.front_ready() {
+            if !self.is_eof() {
+                self.scan_lexeme();
+            } else if !self.finished {
+                self.finish();
+            } else {
+                break;
+            }
+        }
+        self.queue.pop_front()
+    }
+}
+
+/// Digits of the given radix, with `_` separators allowed only between digits.
+fn is_digit_run(text: &str, radix: u32) -> bool {
+    !text.is_empty()
+        && !text.starts_with('_')
+        && !text.ends_with('_')
+        && !text.contains("__")
+        && text.chars().all(|c| c == '_' || c.is_digit(radix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_errors(source: &str) -> Vec<(ScanErrorKind, usize, usize)> {
+        let mut scanner = Scanner::new(source.to_string());
+        match scanner.scan_tokens() {
+            Ok(_) => vec![],
+            Err(errors) => errors.iter()
+                .map(|error| (error.get_kind().clone(), error.get_line(), error.get_column()))
+                .collect(),
+        }
+    }
+
+    fn tokens(source: &str) -> Vec<(TokenType, String)> {
+        Scanner::new(source.to_string())
+            .map(|token| (token.get_type(), token.get_lexeme().clone()))
+            .collect()
+    }
+
+    #[test]
+    fn unexpected_character() {
+        assert_eq!(scan_errors("var a = 1;\n  @"), vec![(ScanErrorKind::UnexpectedCharacter('@'), 2, 3)]);
+    }
+
+    #[test]
+    fn columns_count_characters_not_bytes() {
+        assert_eq!(scan_errors("\"héllo\" @"), vec![(ScanErrorKind::UnexpectedCharacter('@'), 1, 9)]);
+    }
+
+    #[test]
+    fn unterminated_string() {
+        assert_eq!(scan_errors("print 1;\nprint \"abc"), vec![(ScanErrorKind::UnterminatedString, 2, 7)]);
+    }
+
+    #[test]
+    fn unterminated_interpolation() {
+        assert_eq!(scan_errors("\"a ${b + 1"), vec![(ScanErrorKind::UnterminatedInterpolation, 1, 4)]);
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        assert_eq!(scan_errors("1;\n/* a /* b */"), vec![(ScanErrorKind::UnterminatedComment, 2, 1)]);
+    }
+
+    #[test]
+    fn invalid_escape() {
+        assert_eq!(scan_errors("\"ab\\q\""), vec![(ScanErrorKind::InvalidEscape('q'), 1, 4)]);
+    }
+
+    #[test]
+    fn invalid_unicode_escape() {
+        assert_eq!(scan_errors("\"a\n  \\u{zz}\""), vec![(ScanErrorKind::InvalidUnicodeEscape, 2, 3)]);
+    }
+
+    #[test]
+    fn malformed_number() {
+        assert_eq!(scan_errors("var n = 12abc;"), vec![(ScanErrorKind::MalformedNumber, 1, 9)]);
+    }
+
+    fn trivia_texts(trivia: &[Trivia]) -> Vec<(TriviaKind, &str)> {
+        trivia.iter().map(|trivia| (trivia.get_kind(), trivia.get_text())).collect()
+    }
+
+    #[test]
+    fn trivia_round_trips_the_source() {
+        let source = "// header\nvar  x = 1; /* note */\r\n\t@ print x;   \n/* tail */";
+        let mut scanner = Scanner::new(source.to_string()).with_trivia();
+        let _ = scanner.scan_tokens();
+        let text: String = scanner.get_tokens().iter().map(|token| token.full_text(source)).collect();
+        assert_eq!(text, source);
+    }
+
+    #[test]
+    fn trivia_before_the_line_break_trails_the_previous_token() {
+        let source = "a; // one\n  b;";
+        let mut scanner = Scanner::new(source.to_string()).with_trivia();
+        let tokens = scanner.scan_tokens().unwrap();
+        let semicolon = &tokens[1];
+        assert_eq!(
+            trivia_texts(semicolon.get_trailing_trivia()),
+            vec![(TriviaKind::Whitespace, " "), (TriviaKind::LineComment, "// one")]
+        );
+        assert_eq!(
+            trivia_texts(tokens[2].get_leading_trivia()),
+            vec![(TriviaKind::Newline, "\n"), (TriviaKind::Whitespace, "  ")]
+        );
+    }
+
+    #[test]
+    fn number_forms() {
+        for source in ["0x1F", "0XfF_fF", "0b1010", "0o17", "1_000_000", "3.14", "1e10", "2.5E-3", "6e+2"] {
+            assert_eq!(scan_errors(source), vec![], "{}", source);
+            assert_eq!(
+                tokens(source),
+                vec![(TokenType::Number, source.to_string()), (TokenType::Eof, "".to_string())]
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_numbers_still_lex_as_one_number() {
+        for source in ["1__0", "1_", "0b102", "0x", "0o8", "1e", "1e+", "12abc"] {
+            assert_eq!(scan_errors(source), vec![(ScanErrorKind::MalformedNumber, 1, 1)], "{}", source);
+            assert_eq!(tokens(source)[0].0, TokenType::Number, "{}", source);
+            assert_eq!(tokens(source).len(), 2, "{}", source);
+        }
+    }
+
+    #[test]
+    fn interpolation_segments() {
+        let text = |s: &str| s.to_string();
+        assert_eq!(
+            tokens("\"a ${b} c ${d + 1}!\""),
+            vec![
+                (TokenType::Interpolation, text("a ")),
+                (TokenType::Identifier, text("b")),
+                (TokenType::Interpolation, text(" c ")),
+                (TokenType::Identifier, text("d")),
+                (TokenType::Plus, text("+")),
+                (TokenType::Number, text("1")),
+                (TokenType::String, text("!")),
+                (TokenType::Eof, text("")),
+            ]
+        );
+    }
+
+    #[test]
+    fn interpolations_nest_strings_and_braces() {
+        let text = |s: &str| s.to_string();
+        assert_eq!(
+            tokens("\"<${ f(\"${x}\") }> \\${y}\""),
+            vec![
+                (TokenType::Interpolation, text("<")),
+                (TokenType::Identifier, text("f")),
+                (TokenType::LeftParen, text("(")),
+                (TokenType::Interpolation, text("")),
+                (TokenType::Identifier, text("x")),
+                (TokenType::String, text("")),
+                (TokenType::RightParen, text(")")),
+                (TokenType::String, text("> ${y}")),
+                (TokenType::Eof, text("")),
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_strings_keep_backslashes_and_interpolations() {
+        assert_eq!(
+            tokens(r#"r"a\nb ${c}""#),
+            vec![(TokenType::String, r"a\nb ${c}".to_string()), (TokenType::Eof, "".to_string())]
+        );
+    }
+
+    #[test]
+    fn triple_quoted_strings_span_lines() {
+        let mut scanner = Scanner::new("\"\"\"one\n  \"two\"\n\"\"\" x\ny".to_string());
+        let tokens: Vec<(TokenType, String, usize)> = scanner.by_ref()
+            .map(|token| (token.get_type(), token.get_lexeme().clone(), token.get_line()))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenType::String, "one\n  \"two\"\n".to_string(), 1),
+                (TokenType::Identifier, "x".to_string(), 3),
+                (TokenType::Identifier, "y".to_string(), 4),
+                (TokenType::Eof, "".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_raw_strings() {
+        assert_eq!(scan_errors("1;\n  r\"abc"), vec![(ScanErrorKind::UnterminatedString, 2, 3)]);
+        assert_eq!(scan_errors("1;\n\"\"\"abc\n\"\""), vec![(ScanErrorKind::UnterminatedString, 2, 1)]);
+    }
+
+    #[test]
+    fn the_iterator_lexes_on_demand() {
+        let mut scanner = Scanner::new("1 + 2 @".to_string());
+        assert_eq!(scanner.next().map(|token| token.get_type()), Some(TokenType::Number));
+        assert_eq!(scanner.next().map(|token| token.get_type()), Some(TokenType::Plus));
+        assert!(scanner.get_errors().is_empty());
+
+        let rest: Vec<TokenType> = scanner.by_ref().map(|token| token.get_type()).collect();
+        assert_eq!(rest, vec![TokenType::Number, TokenType::Eof]);
+        assert_eq!(scanner.get_errors().len(), 1);
+        assert!(scanner.next().is_none());
+    }
+
+    #[test]
+    fn unicode_identifiers() {
+        assert_eq!(
+            tokens("π_2 Δx ünï"),
+            vec![
+                (TokenType::Identifier, "π_2".to_string()),
+                (TokenType::Identifier, "Δx".to_string()),
+                (TokenType::Identifier, "ünï".to_string()),
+                (TokenType::Eof, "".to_string()),
+            ]
+        );
+        assert_eq!(scan_errors("a€"), vec![(ScanErrorKind::UnexpectedCharacter('€'), 1, 2)]);
+    }
+
+    #[test]
+    fn identifiers_are_nfc_normalized() {
+        let composed = tokens("caf\u{e9}");
+        let decomposed = tokens("cafe\u{301}");
+        assert_eq!(composed, decomposed);
+        assert_eq!(composed[0].1, "caf\u{e9}");
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use super::source_map::Span;
+
+/// What went wrong while lexing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanErrorKind {
+    /// A character that does not start any token, e.g. `@`
+    UnexpectedCharacter(char),
+    /// A string literal with no closing `"`
+    UnterminatedString,
+    /// A `${` inside a string with no matching `}`
+    UnterminatedInterpolation,
+    /// A `/*` comment with no matching `*/`
+    UnterminatedComment,
+    /// A `\` followed by a character that is not a known escape, e.g. `\q`
+    InvalidEscape(char),
+    /// A `\u` escape that is not `\u{XXXX}` with 1-6 hex digits naming a valid code point
+    InvalidUnicodeEscape,
+    /// A number literal that cannot be read, e.g. `12abc`
+    MalformedNumber,
+}
+
+/// A lexical error with the byte span of the offending text and
+/// the (1-based) line and column where it starts.
+#[derive(Debug, Clone)]
+pub struct ScanError {
+    kind: ScanErrorKind,
+    span: Span,
+    line: usize,
+    column: usize,
+}
+
+impl ScanError {
+    pub fn new(kind: ScanErrorKind, span: Span, line: usize, column: usize) -> ScanError {
+        ScanError { kind, span, line, column }
+    }
+
+    pub fn get_kind(&self) -> &ScanErrorKind { &self.kind }
+    pub fn get_span(&self) -> Span { self.span }
+    pub fn get_start(&self) -> usize { self.span.get_start() }
+    pub fn get_end(&self) -> usize { self.span.get_end() }
+    pub fn get_line(&self) -> usize { self.line }
+    pub fn get_column(&self) -> usize { self.column }
+}
+
+impl Display for ScanErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanErrorKind::UnexpectedCharacter(c) => write!(f, "Unexpected character '{}'", c),
+            ScanErrorKind::UnterminatedString => write!(f, "Unterminated string"),
+            ScanErrorKind::UnterminatedInterpolation => write!(f, "Unterminated '${{' in string"),
+            ScanErrorKind::UnterminatedComment => write!(f, "Unterminated block comment"),
+            ScanErrorKind::InvalidEscape(c) => write!(f, "Invalid escape sequence '\\{}'", c.escape_default()),
+            ScanErrorKind::InvalidUnicodeEscape => write!(f, "Invalid unicode escape, expected '\\u{{XXXX}}'"),
+            ScanErrorKind::MalformedNumber => write!(f, "Malformed number"),
+        }
+    }
+}
+
+impl Display for ScanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[line {}, column {}] Scan error: {}", self.line, self.column, self.kind)
+    }
+}
@@ -0,0 +1,79 @@
+use std::rc::Rc;
+
+/// Identifies one source file inside a `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(usize);
+
+/// A byte range `start..end` inside one source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    file: FileId,
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    pub fn new(file: FileId, start: usize, end: usize) -> Span {
+        Span { file, start, end }
+    }
+
+    pub fn get_file(&self) -> FileId { self.file }
+    pub fn get_start(&self) -> usize { self.start }
+    pub fn get_end(&self) -> usize { self.end }
+}
+
+pub struct SourceFile {
+    name: String,
+    source: Rc<str>,
+    line_starts: Vec<usize>, // byte offset of the first char of every line
+}
+
+impl SourceFile {
+    fn new(name: String, source: Rc<str>) -> SourceFile {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        SourceFile { name, source, line_starts }
+    }
+
+    pub fn get_name(&self) -> &str { &self.name }
+    pub fn get_source(&self) -> &Rc<str> { &self.source }
+
+    /// 1-based line and column (counted in chars) of a byte offset.
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.source[line_start..offset].chars().count() + 1;
+        (line, column)
+    }
+}
+
+/// Owns every source file of a run and maps spans back to text and positions.
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap { files: vec![] }
+    }
+
+    pub fn add_file(&mut self, name: String, source: String) -> FileId {
+        self.files.push(SourceFile::new(name, Rc::from(source)));
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn get_file(&self, file: FileId) -> &SourceFile {
+        &self.files[file.0]
+    }
+
+    /// 1-based line and column where the span starts.
+    pub fn line_column(&self, span: Span) -> (usize, usize) {
+        self.get_file(span.file).line_column(span.start)
+    }
+
+    pub fn span_text(&self, span: Span) -> &str {
+        &self.get_file(span.file).source[span.start..span.end]
+    }
+}
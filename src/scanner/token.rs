@@ -1,37 +1,64 @@
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use super::source_map::Span;
 use super::token_type::TokenType;
+use super::trivia::Trivia;
 
 #[derive(Clone)]
 pub struct Token{
     token_type: TokenType,
     lexeme: String,
-    start: usize,
-    end: usize,
+    span: Span,
     line: usize,
+    column: usize,
+    // only filled in when the scanner keeps trivia
+    leading_trivia: Vec<Trivia>,
+    trailing_trivia: Vec<Trivia>,
 }
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, start: usize, end: usize, line: usize) -> Token {
-        Token{token_type, lexeme, start, end, line}
+    pub fn new(token_type: TokenType, lexeme: String, span: Span, line: usize, column: usize) -> Token {
+        Token{token_type, lexeme, span, line, column, leading_trivia: vec![], trailing_trivia: vec![]}
     }
 
     pub fn get_type(&self) -> TokenType { self.token_type.clone() }
     pub fn get_lexeme(&self) -> &String { &self.lexeme }
-    pub fn get_start(&self) -> usize { self.start }
-    pub fn get_end(&self) -> usize { self.end }
+    pub fn get_span(&self) -> Span { self.span }
+    pub fn get_start(&self) -> usize { self.span.get_start() }
+    pub fn get_end(&self) -> usize { self.span.get_end() }
     pub fn get_line(&self) -> usize { self.line }
+    pub fn get_column(&self) -> usize { self.column }
+    pub fn get_leading_trivia(&self) -> &Vec<Trivia> { &self.leading_trivia }
+    pub fn get_trailing_trivia(&self) -> &Vec<Trivia> { &self.trailing_trivia }
+
+    pub(crate) fn set_leading_trivia(&mut self, trivia: Vec<Trivia>) { self.leading_trivia = trivia; }
+    pub(crate) fn trailing_trivia_mut(&mut self) -> &mut Vec<Trivia> { &mut self.trailing_trivia }
+
+    /// The token's source text together with its trivia. Concatenating this
+    /// for every token of a trivia-keeping scan gives back the whole source.
+    pub fn full_text(&self, source: &str) -> String {
+        let mut text = String::new();
+        for trivia in &self.leading_trivia {
+            text.push_str(trivia.get_text());
+        }
+        text.push_str(&source[self.get_start()..self.get_end()]);
+        for trivia in &self.trailing_trivia {
+            text.push_str(trivia.get_text());
+        }
+        text
+    }
 }
 
 impl Debug for Token {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Token {{ type: {:?}, lexeme: \"{}\", start: {}, end: {}, line: {} }}",
+            "Token {{ type: {:?}, lexeme: \"{}\", start: {}, end: {}, line: {}, column: {} }}",
             self.token_type,
             self.lexeme,
-            self.start,
-            self.end,
-            self.line
+            self.span.get_start(),
+            self.span.get_end(),
+            self.line,
+            self.column
         )
     }
 }
\ No newline at end of file
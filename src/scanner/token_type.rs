@@ -13,6 +13,8 @@ pub enum TokenType{
 
     // Literals
     Identifier, String, Number,
+    // string text that is followed by an embedded `${ expression }`
+    Interpolation,
 
     //Keywords
     Class, Super, This, Fun, Var,
@@ -0,0 +1,28 @@
+use super::source_map::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    Whitespace,   // spaces, tabs and carriage returns
+    Newline,
+    LineComment,  // `// ...`, without the line break
+    BlockComment, // `/* ... */`
+    Skipped,      // text that failed to lex, kept so nothing is lost
+}
+
+/// Source text between tokens that the parser never sees.
+#[derive(Debug, Clone)]
+pub struct Trivia {
+    kind: TriviaKind,
+    text: String,
+    span: Span,
+}
+
+impl Trivia {
+    pub fn new(kind: TriviaKind, text: String, span: Span) -> Trivia {
+        Trivia { kind, text, span }
+    }
+
+    pub fn get_kind(&self) -> TriviaKind { self.kind }
+    pub fn get_text(&self) -> &str { &self.text }
+    pub fn get_span(&self) -> Span { self.span }
+}